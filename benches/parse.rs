@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nv_rom_parser::firmware::FirmwareBundleInfo;
+use nv_rom_parser::RegionStructureIterator;
+use std::io::{Cursor, Read};
+
+#[path = "../src/dev.rs"]
+mod dev;
+
+const FIXTURES: [(&str, &str); 2] = [
+    (
+        "4090",
+        "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+    ),
+    (
+        "3060Ti",
+        "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+    ),
+];
+
+fn load_fixture(url: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    dev::get_rom_file(url)?.read_to_end(&mut bytes).unwrap();
+    Some(bytes)
+}
+
+fn fixtures() -> Vec<(&'static str, Vec<u8>)> {
+    FIXTURES
+        .iter()
+        .filter_map(|(name, url)| load_fixture(url).map(|bytes| (*name, bytes)))
+        .collect()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, bytes) in fixtures() {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| FirmwareBundleInfo::parse(&mut Cursor::new(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_structure_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("structure_scan");
+    // Worst case without any structure, every probe position is tested
+    let mut inputs = vec![("empty-1MiB", vec![0u8; 1024 * 1024])];
+    inputs.extend(fixtures());
+    for (name, bytes) in inputs {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| {
+                let mut cursor = Cursor::new(bytes);
+                RegionStructureIterator::new(&mut cursor).count()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_structure_scan);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nv_rom_parser::firmware::FirmwareBundleInfo;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = FirmwareBundleInfo::parse_bytes(data);
+});
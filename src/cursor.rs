@@ -1,8 +1,8 @@
 // SPDX-License-Identifier: MIT
 
-use crate::FirmwareRegion;
+use crate::{Error, FirmwareRegion};
 use log::trace;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::ops::{AddAssign, SubAssign};
 
 pub struct ContinuousRegionReader<'a, S> {
@@ -10,6 +10,7 @@ pub struct ContinuousRegionReader<'a, S> {
     pub translated_stream_position: u64,
 
     pub regions: Vec<&'a dyn FirmwareRegion>,
+    zero_fill: bool,
 }
 
 impl<'a, S> ContinuousRegionReader<'a, S> {
@@ -19,9 +20,57 @@ impl<'a, S> ContinuousRegionReader<'a, S> {
             source,
             translated_stream_position: 0,
             regions,
+            zero_fill: false,
         }
     }
 
+    /// Reads zeros past the end of the regions and of the source instead of stopping,
+    /// so trailing fields of fixed-size structures decode as zeros
+    pub fn with_zero_fill(mut self) -> Self {
+        self.zero_fill = true;
+        self
+    }
+
+    pub fn try_new(source: &'a mut S, regions: Vec<&'a dyn FirmwareRegion>) -> crate::Result<Self> {
+        let reader = Self::new(source, regions);
+        for pair in reader.regions.windows(2) {
+            if pair[0].end_offset_in_firmware() > pair[1].offset_in_firmware() {
+                return Err(Error::ErrorMessage(format!(
+                    "Region {}..{} overlaps region {}..{}",
+                    pair[0].offset_in_firmware(),
+                    pair[0].end_offset_in_firmware(),
+                    pair[1].offset_in_firmware(),
+                    pair[1].end_offset_in_firmware()
+                )));
+            }
+        }
+        Ok(reader)
+    }
+
+    pub fn logical_to_firmware(&self, logical: u64) -> Option<u64> {
+        self.logical_region_offset(logical)
+            .map(|(region, offset)| region.offset_in_firmware() + offset)
+    }
+
+    /// The region that the logical position falls in
+    pub fn logical_region(&self, logical: u64) -> Option<&'a dyn FirmwareRegion> {
+        self.logical_region_offset(logical)
+            .map(|(region, _)| region)
+    }
+
+    /// The region that the logical position falls in and the offset inside it
+    fn logical_region_offset(&self, logical: u64) -> Option<(&'a dyn FirmwareRegion, u64)> {
+        let mut remaining_offset = logical;
+        for region in &self.regions {
+            let region_size = region.region_size();
+            if region_size > remaining_offset {
+                return Some((*region, remaining_offset));
+            }
+            remaining_offset.sub_assign(region_size);
+        }
+        None
+    }
+
     fn reader_position_info(&self, firmware_position: u64) -> ReaderPositionInfo<'a> {
         let mut current_region_translated_offset = 0u64;
         let mut end_offset_in_firmware = 0u64;
@@ -82,7 +131,12 @@ impl<'a, S: Read + Seek> Read for ContinuousRegionReader<'a, S> {
             } => {
                 let bytes_left_to_read = region.region_size() - offset;
                 let buf_len = buf.len().min(bytes_left_to_read as usize);
-                let read_count = self.source.read(&mut buf[..buf_len])?;
+                let mut read_count = self.source.read(&mut buf[..buf_len])?;
+                if read_count == 0 && self.zero_fill {
+                    buf[..buf_len].fill(0);
+                    self.source.seek(SeekFrom::Current(buf_len as i64))?;
+                    read_count = buf_len;
+                }
                 if read_count == bytes_left_to_read as usize {
                     if let Some(next_region) = self.regions.get(region_index + 1) {
                         self.source
@@ -91,6 +145,11 @@ impl<'a, S: Read + Seek> Read for ContinuousRegionReader<'a, S> {
                 }
                 Ok(read_count)
             }
+            ReaderPositionInfo::AfterLastRegion { .. } if self.zero_fill => {
+                buf.fill(0);
+                self.source.seek(SeekFrom::Current(buf.len() as i64))?;
+                Ok(buf.len())
+            }
             ReaderPositionInfo::AfterLastRegion { .. } => Ok(0),
             ReaderPositionInfo::BeforeFirstRegion => Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -130,7 +189,8 @@ impl<'a, S: Read + Seek> Seek for ContinuousRegionReader<'a, S> {
                     .map(|r| r.end_offset_in_firmware())
                     .unwrap_or(0);
                 self.source
-                    .seek(SeekFrom::Start(last_region_end_offset + remaining_offset))
+                    .seek(SeekFrom::Start(last_region_end_offset + remaining_offset))?;
+                Ok(from_start)
             }
             SeekFrom::End(from_end) => {
                 let total_regions_size: u64 = self.regions.iter().map(|r| r.region_size()).sum();
@@ -180,10 +240,89 @@ impl<'a, S: Read + Seek> Seek for ContinuousRegionReader<'a, S> {
     }
 }
 
+/// Buffers a `Read`-only stream, e.g. a pipe, in memory to provide `Seek` for the parser
+pub struct BufferedSeekable {
+    cursor: Cursor<Vec<u8>>,
+}
+
+impl BufferedSeekable {
+    /// Reads the whole stream, fails if it is longer than `max_size` bytes
+    pub fn new<R: Read>(source: R, max_size: u64) -> crate::Result<Self> {
+        let mut data = Vec::new();
+        source
+            .take(max_size.saturating_add(1))
+            .read_to_end(&mut data)?;
+        if data.len() as u64 > max_size {
+            return Err(Error::InvalidFormat(format!(
+                "Stream is longer than {} bytes",
+                max_size
+            )));
+        }
+        trace!("Buffered {} bytes of the stream", data.len());
+        Ok(Self {
+            cursor: Cursor::new(data),
+        })
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.cursor.into_inner()
+    }
+}
+
+impl Read for BufferedSeekable {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl Seek for BufferedSeekable {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+/// View of the source that starts at `offset`, positions before it are not reachable
+pub struct OffsetReader<'a, S> {
+    source: &'a mut S,
+    offset: u64,
+}
+
+impl<'a, S: Seek> OffsetReader<'a, S> {
+    pub fn new(source: &'a mut S, offset: u64) -> Self {
+        Self { source, offset }
+    }
+}
+
+impl<'a, S: Read> Read for OffsetReader<'a, S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.source.read(buf)
+    }
+}
+
+impl<'a, S: Seek> Seek for OffsetReader<'a, S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => SeekFrom::Start(pos + self.offset),
+            pos => pos,
+        };
+        let position = self.source.seek(pos)?;
+        match position.checked_sub(self.offset) {
+            Some(position) => Ok(position),
+            None => {
+                self.source.seek(SeekFrom::Start(self.offset))?;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Seek before the start of the reader",
+                ))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::cursor::ContinuousRegionReader;
-    use crate::FirmwareRegion;
+    use crate::cursor::{BufferedSeekable, ContinuousRegionReader, OffsetReader};
+    use crate::{Error, FirmwareRegion};
     use std::io::{Cursor, Read, Seek, SeekFrom};
 
     #[derive(Debug)]
@@ -202,6 +341,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_offset_reader() {
+        let mut cursor = Cursor::new(Vec::from_iter(0u8..100));
+        let mut reader = OffsetReader::new(&mut cursor, 10);
+
+        assert_eq!(90, reader.seek(SeekFrom::End(0)).unwrap());
+        assert_eq!(5, reader.seek(SeekFrom::Start(5)).unwrap());
+        let mut buf = [0u8; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!([15, 16], buf);
+        assert!(reader.seek(SeekFrom::Current(-10)).is_err());
+        assert_eq!(0, reader.stream_position().unwrap());
+    }
+
     #[test]
     fn test_read() {
         let data = Vec::from_iter(0u8..100);
@@ -252,6 +405,32 @@ mod tests {
         assert!(reader.read_exact(&mut buf[..5]).is_err());
     }
 
+    #[test]
+    fn test_read_past_end() {
+        let data = Vec::from_iter(0u8..20);
+        let region_1 = TestRegion { start: 0, size: 4 };
+        let region_2 = TestRegion { start: 8, size: 4 };
+        let region_3 = TestRegion { start: 16, size: 8 };
+        let regions: Vec<&dyn FirmwareRegion> = vec![&region_1, &region_2, &region_3];
+        let mut buf = [0xFFu8; 6];
+
+        let mut cursor = Cursor::new(data.as_slice());
+        let mut reader = ContinuousRegionReader::new(&mut cursor, regions.clone());
+        reader.seek(SeekFrom::Start(8)).unwrap();
+        assert!(reader.read_exact(&mut buf).is_err());
+        assert_eq!(20, reader.seek(SeekFrom::Start(20)).unwrap());
+        assert_eq!(0, reader.read(&mut buf).unwrap());
+
+        let mut cursor = Cursor::new(data.as_slice());
+        let mut reader = ContinuousRegionReader::new(&mut cursor, regions).with_zero_fill();
+        reader.seek(SeekFrom::Start(8)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!([16, 17, 18, 19, 0, 0], buf);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!([0u8; 6], buf);
+        assert_eq!(20, reader.stream_position().unwrap());
+    }
+
     #[test]
     fn test_seek() {
         let data = Vec::from_iter(0u8..100);
@@ -329,4 +508,67 @@ mod tests {
         reader.read_exact(&mut buf).unwrap();
         assert_eq!(0, buf[0]);
     }
+
+    #[test]
+    fn test_logical_to_firmware() {
+        let data = Vec::from_iter(0u8..100);
+        let region_1 = TestRegion { start: 0, size: 10 };
+        let region_3 = TestRegion {
+            start: 15,
+            size: 35,
+        };
+        let region_5 = TestRegion {
+            start: 80,
+            size: 10,
+        };
+
+        let mut cursor = Cursor::new(data.as_slice());
+        // [0..10; 15..50; 80..90]
+        let reader =
+            ContinuousRegionReader::new(&mut cursor, vec![&region_1, &region_3, &region_5]);
+
+        assert_eq!(Some(0), reader.logical_to_firmware(0));
+        assert_eq!(Some(9), reader.logical_to_firmware(9));
+        assert_eq!(Some(15), reader.logical_to_firmware(10));
+        assert_eq!(Some(49), reader.logical_to_firmware(44));
+        assert_eq!(Some(80), reader.logical_to_firmware(45));
+        assert_eq!(Some(89), reader.logical_to_firmware(54));
+        assert_eq!(None, reader.logical_to_firmware(55));
+    }
+
+    #[test]
+    fn test_try_new_overlapping() {
+        let data = Vec::from_iter(0u8..100);
+        let region_1 = TestRegion { start: 0, size: 10 };
+        let region_2 = TestRegion { start: 10, size: 5 };
+        let region_3 = TestRegion { start: 12, size: 8 };
+
+        let mut cursor = Cursor::new(data.as_slice());
+        assert!(ContinuousRegionReader::try_new(&mut cursor, vec![&region_2, &region_1]).is_ok());
+        assert!(ContinuousRegionReader::try_new(
+            &mut cursor,
+            vec![&region_3, &region_1, &region_2]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_buffered_seekable() {
+        let data = Vec::from_iter(0u8..100);
+        let mut reader = BufferedSeekable::new(data.as_slice(), 100).unwrap();
+        let mut buf = [0u8; 4];
+
+        reader.seek(SeekFrom::Start(50)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!([50, 51, 52, 53], buf);
+        reader.seek(SeekFrom::Current(-10)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!([44, 45, 46, 47], buf);
+        assert_eq!(data, reader.into_inner());
+
+        assert!(matches!(
+            BufferedSeekable::new(data.as_slice(), 99),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
 }
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+
+//! ROM files of the tests and benchmarks, the benchmarks include this file by path
+
+use reqwest::Url;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
+
+const CACHE_FOLDER: &str = "nv-rom-parser-cache";
+const ROM_DIR_ENV: &str = "NV_ROM_PARSER_ROM_DIR";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_ROM_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Returns the ROM file from the directory in `NV_ROM_PARSER_ROM_DIR` or from the download cache.
+/// `None` means that the ROM is not available locally and cannot be downloaded, so the test or benchmark should be skipped.
+pub fn get_rom_file(url: &str) -> Option<File> {
+    let url = Url::parse(url).unwrap();
+    let filename = url.path_segments().unwrap().last().unwrap().to_string();
+
+    if let Some(rom_dir) = env::var_os(ROM_DIR_ENV) {
+        let path = PathBuf::from(rom_dir).join(&filename);
+        if path.exists() {
+            return Some(File::open(&path).unwrap());
+        }
+    }
+
+    let cache_dir = env::temp_dir().join(CACHE_FOLDER);
+    let path = cache_dir.join(&filename);
+    if !cache_dir.exists() {
+        fs::create_dir_all(&cache_dir).unwrap();
+    }
+    if !is_valid_rom_file(&path) && !download_rom_file(&url, &path) {
+        eprintln!("Skipping, {} is not available offline", filename);
+        return None;
+    }
+    Some(File::open(&path).unwrap())
+}
+
+fn is_valid_rom_file(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|metadata| {
+            let len = metadata.len();
+            len > 0 && len % 512 == 0 && len <= MAX_ROM_FILE_SIZE
+        })
+        .unwrap_or(false)
+}
+
+/// Returns `false` if the ROM cannot be downloaded, panics if the downloaded file is corrupt.
+fn download_rom_file(url: &Url, path: &Path) -> bool {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .unwrap();
+    let mut response = match client.get(url.clone()).send() {
+        Ok(response) => response,
+        Err(err) => {
+            eprintln!("Failed to download {}: {}", url, err);
+            return false;
+        }
+    };
+    if !response.status().is_success() {
+        eprintln!("Failed to download {}: {}", url, response.status());
+        return false;
+    }
+    let expected_len = response.content_length();
+    if let Some(expected_len) = expected_len {
+        assert!(
+            expected_len <= MAX_ROM_FILE_SIZE,
+            "ROM file {} is too large: {} bytes",
+            url,
+            expected_len
+        );
+    }
+
+    let part_path = path.with_extension("part");
+    let mut file = File::create(&part_path).unwrap();
+    let copied = match response.copy_to(&mut file) {
+        Ok(copied) => copied,
+        Err(err) => {
+            eprintln!("Failed to download {}: {}", url, err);
+            fs::remove_file(&part_path).ok();
+            return false;
+        }
+    };
+    drop(file);
+
+    if expected_len.is_some_and(|expected_len| expected_len != copied)
+        || !is_valid_rom_file(&part_path)
+    {
+        fs::remove_file(&part_path).ok();
+        panic!("Corrupt download of {}: {} bytes received", url, copied);
+    }
+    fs::rename(&part_path, path).unwrap();
+    true
+}
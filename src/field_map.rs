@@ -0,0 +1,602 @@
+// SPDX-License-Identifier: MIT
+
+use crate::firmware::{FirmwareBundleInfo, LegacyPciImageInfo};
+use crate::nvidia::bit::perf::{MemoryClockTable, MemoryTweakTable};
+use crate::nvidia::bit::BITTokenType;
+use crate::FirmwareRegion;
+use serde::Serialize;
+
+const MEMORY_CLOCK_TABLE_HEADER_FIELDS: &[(&str, u64)] = &[
+    ("version", 1),
+    ("header_size", 1),
+    ("base_entry_size", 1),
+    ("strap_entry_size", 1),
+    ("strap_entry_count", 1),
+    ("entry_count", 1),
+];
+const MEMORY_CLOCK_TABLE_BASE_ENTRY_FIELDS: &[(&str, u64)] =
+    &[("min_freq", 2), ("max_freq", 2), ("reserved", 4)];
+const MEMORY_CLOCK_TABLE_STRAP_ENTRY_FIELDS: &[(&str, u64)] = &[
+    ("mem_tweak_index", 1),
+    ("flags_0", 1),
+    ("reserved_0", 6),
+    ("flags_4", 1),
+    ("reserved_1", 1),
+    ("flags_5", 1),
+];
+
+const MEMORY_TWEAK_TABLE_HEADER_FIELDS: &[(&str, u64)] = &[
+    ("version", 1),
+    ("header_size", 1),
+    ("base_entry_size", 1),
+    ("extended_entry_size", 1),
+    ("extended_entry_count", 1),
+    ("entry_count", 1),
+];
+// Bit sizes of the bitfield structures, in declaration order
+const MEMORY_TWEAK_TABLE_BASE_ENTRY_BIT_FIELDS: &[(&str, &[(&str, u64)])] = &[
+    (
+        "config_0",
+        &[
+            ("rc", 8),
+            ("rfc", 9),
+            ("ras", 7),
+            ("rp", 7),
+            ("reserved_0", 1),
+        ],
+    ),
+    (
+        "config_1",
+        &[
+            ("cl", 7),
+            ("wl", 7),
+            ("rd_rcd", 6),
+            ("wr_rcd", 6),
+            ("reserved_1", 6),
+        ],
+    ),
+    (
+        "config_2",
+        &[
+            ("rpre", 4),
+            ("wpre", 4),
+            ("cdlr", 7),
+            ("reserved_3", 1),
+            ("wr", 7),
+            ("reserved_4", 1),
+            ("w2r_bus", 4),
+            ("r2w_bus", 4),
+        ],
+    ),
+    (
+        "config_3",
+        &[
+            ("pdex", 5),
+            ("pden2pdex", 4),
+            ("faw", 8),
+            ("aond", 7),
+            ("ccdl", 4),
+            ("ccds", 4),
+        ],
+    ),
+    (
+        "config_4",
+        &[
+            ("refresh_lo", 3),
+            ("refresh", 12),
+            ("rrd", 6),
+            ("delay_0", 6),
+            ("reserved_5", 5),
+        ],
+    ),
+    (
+        "config_5",
+        &[
+            ("adr_min", 3),
+            ("reserved_6", 1),
+            ("wrcrc", 7),
+            ("reserved_7", 1),
+            ("offset_0", 6),
+            ("delay_0_msb", 2),
+            ("offset_1", 4),
+            ("offset_2", 4),
+            ("delay_0_1", 4),
+        ],
+    ),
+    ("reserved_0", &[]),
+    (
+        "voltage_config",
+        &[
+            ("drive_strength", 2),
+            ("voltage_0", 3),
+            ("voltage_1", 3),
+            ("voltage_2", 3),
+            ("r2p", 5),
+            ("voltage_3", 3),
+            ("reserved_0", 1),
+            ("voltage_4", 3),
+            ("reserved_1", 1),
+            ("voltage_5", 3),
+            ("reserved_2", 5),
+            ("rdcrc", 4),
+            ("reserved_3", 36),
+        ],
+    ),
+    (
+        "timing_config",
+        &[("rfcsba", 10), ("rfcsbr", 8), ("reserved", 14)],
+    ),
+    ("reserved_1", &[]),
+];
+const MEMORY_TWEAK_TABLE_RESERVED_0_SIZE: u64 = 23;
+const MEMORY_TWEAK_TABLE_RESERVED_1_SIZE: u64 = 16;
+
+/// Byte range of a single parsed leaf field.
+/// `field_path` follows the serialized structure, e.g. `firmwares[0].legacy_pci_image.memory_clock_table.entries[1].base_entry.min_freq`.
+/// Bitfield members share bytes, so their ranges cover all bytes containing at least one bit of the field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldRange {
+    pub start: u64,
+    pub len: u64,
+    pub field_path: String,
+}
+
+/// Maps firmware file offsets to the parsed field names.
+/// Currently covers the memory clock and memory tweak tables.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FieldMap {
+    pub fields: Vec<FieldRange>,
+}
+
+impl FieldMap {
+    pub fn new(bundle: &FirmwareBundleInfo) -> Self {
+        let mut field_map = FieldMap::default();
+        for (index, firmware) in bundle.firmwares.iter().enumerate() {
+            if let Some(image) = &firmware.legacy_pci_image {
+                let mut regions: Vec<&dyn FirmwareRegion> = vec![&image.image];
+                for nv in &firmware.nv_pci_expansion_roms {
+                    regions.push(nv);
+                }
+                regions.sort_by_key(|r| r.offset_in_firmware());
+                let mut builder = FieldMapBuilder {
+                    regions,
+                    fields: &mut field_map.fields,
+                };
+                builder
+                    .add_legacy_pci_image(image, &format!("firmwares[{}].legacy_pci_image", index));
+            }
+        }
+        field_map.fields.sort_by_key(|f| f.start);
+        field_map
+    }
+
+    pub fn fields_at(&self, offset: u64) -> impl Iterator<Item = &FieldRange> {
+        self.fields
+            .iter()
+            .filter(move |f| f.start <= offset && offset < f.start + f.len)
+    }
+}
+
+struct FieldMapBuilder<'a> {
+    regions: Vec<&'a dyn FirmwareRegion>,
+    fields: &'a mut Vec<FieldRange>,
+}
+
+impl<'a> FieldMapBuilder<'a> {
+    fn add_legacy_pci_image(&mut self, image: &LegacyPciImageInfo, prefix: &str) {
+        let perf_ptrs = image.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::Perf(ptrs) => Some(*ptrs),
+            _ => None,
+        });
+        if let Some(ptrs) = perf_ptrs {
+            if let Some(table) = &image.memory_clock_table {
+                self.add_memory_clock_table(
+                    table,
+                    ptrs.memory_clock_table_ptr as u64,
+                    &format!("{}.memory_clock_table", prefix),
+                );
+            }
+            if let Some(table) = &image.memory_tweak_table {
+                self.add_memory_tweak_table(
+                    table,
+                    ptrs.memory_tweak_table_ptr as u64,
+                    &format!("{}.memory_tweak_table", prefix),
+                );
+            }
+        }
+    }
+
+    fn add_memory_clock_table(&mut self, table: &MemoryClockTable, start: u64, prefix: &str) {
+        let header_size = table.header.header_size as u64;
+        let base_entry_size = table.header.base_entry_size as u64;
+        let strap_entry_size = table.header.strap_entry_size as u64;
+        let strap_entry_count = table.header.strap_entry_count as u64;
+        let entry_size = base_entry_size + strap_entry_size * strap_entry_count;
+
+        let header_unknown_start = self.add_fields(
+            start,
+            &format!("{}.header", prefix),
+            MEMORY_CLOCK_TABLE_HEADER_FIELDS,
+        );
+        self.add_field(
+            header_unknown_start,
+            start + header_size - header_unknown_start,
+            format!("{}.header.unknown", prefix),
+        );
+        for index in 0..table.entries.len() as u64 {
+            let entry_start = start + header_size + index * entry_size;
+            let entry_prefix = format!("{}.entries[{}]", prefix, index);
+            let base_prefix = format!("{}.base_entry", entry_prefix);
+            let unknown_start = self.add_fields(
+                entry_start,
+                &base_prefix,
+                MEMORY_CLOCK_TABLE_BASE_ENTRY_FIELDS,
+            );
+            self.add_field(
+                unknown_start,
+                entry_start + base_entry_size - unknown_start,
+                format!("{}.unknown", base_prefix),
+            );
+            for strap_index in 0..strap_entry_count {
+                let strap_start = entry_start + base_entry_size + strap_index * strap_entry_size;
+                let strap_prefix = format!("{}.strap_entries[{}]", entry_prefix, strap_index);
+                let unknown_start = self.add_fields(
+                    strap_start,
+                    &strap_prefix,
+                    MEMORY_CLOCK_TABLE_STRAP_ENTRY_FIELDS,
+                );
+                self.add_field(
+                    unknown_start,
+                    strap_start + strap_entry_size - unknown_start,
+                    format!("{}.unknown", strap_prefix),
+                );
+            }
+        }
+    }
+
+    fn add_memory_tweak_table(&mut self, table: &MemoryTweakTable, start: u64, prefix: &str) {
+        let header_size = table.header.header_size as u64;
+        let base_entry_size = table.header.base_entry_size as u64;
+        let extended_entry_size = table.header.extended_entry_size as u64;
+        let extended_entry_count = table.header.extended_entry_count as u64;
+        let entry_size = base_entry_size + extended_entry_size * extended_entry_count;
+
+        let header_unknown_start = self.add_fields(
+            start,
+            &format!("{}.header", prefix),
+            MEMORY_TWEAK_TABLE_HEADER_FIELDS,
+        );
+        self.add_field(
+            header_unknown_start,
+            start + header_size - header_unknown_start,
+            format!("{}.header.unknown", prefix),
+        );
+        for index in 0..table.entries.len() as u64 {
+            let entry_start = start + header_size + index * entry_size;
+            let entry_prefix = format!("{}.entries[{}]", prefix, index);
+            let mut field_start = entry_start;
+            for (name, bit_fields) in MEMORY_TWEAK_TABLE_BASE_ENTRY_BIT_FIELDS {
+                let field_prefix = format!("{}.base_entry.{}", entry_prefix, name);
+                let size = match *name {
+                    "reserved_0" => MEMORY_TWEAK_TABLE_RESERVED_0_SIZE,
+                    "reserved_1" => MEMORY_TWEAK_TABLE_RESERVED_1_SIZE,
+                    _ => bit_fields.iter().map(|(_, bits)| bits).sum::<u64>() / 8,
+                };
+                if bit_fields.is_empty() {
+                    self.add_field(field_start, size, field_prefix);
+                } else {
+                    self.add_bit_fields(field_start, &field_prefix, bit_fields);
+                }
+                field_start += size;
+            }
+            for extended_index in 0..extended_entry_count {
+                self.add_field(
+                    entry_start + base_entry_size + extended_index * extended_entry_size,
+                    extended_entry_size,
+                    format!(
+                        "{}.extended_entries[{}].unknown",
+                        entry_prefix, extended_index
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Adds consecutive byte fields, returns the offset after the last one.
+    fn add_fields(&mut self, start: u64, prefix: &str, fields: &[(&str, u64)]) -> u64 {
+        let mut field_start = start;
+        for (name, size) in fields {
+            self.add_field(field_start, *size, format!("{}.{}", prefix, name));
+            field_start += size;
+        }
+        field_start
+    }
+
+    fn add_bit_fields(&mut self, start: u64, prefix: &str, fields: &[(&str, u64)]) {
+        let mut bit_offset = 0u64;
+        for (name, bits) in fields {
+            let first_byte = bit_offset / 8;
+            let last_byte = (bit_offset + bits - 1) / 8;
+            self.add_field(
+                start + first_byte,
+                last_byte - first_byte + 1,
+                format!("{}.{}", prefix, name),
+            );
+            bit_offset += bits;
+        }
+    }
+
+    /// Adds a field by its offset in the legacy image, splitting it when it crosses a region boundary.
+    fn add_field(&mut self, logical_start: u64, len: u64, field_path: String) {
+        let mut logical_start = logical_start;
+        let mut remaining_len = len;
+        let mut region_logical_start = 0u64;
+        for region in &self.regions {
+            let region_size = region.region_size();
+            let region_logical_end = region_logical_start + region_size;
+            if remaining_len > 0 && logical_start < region_logical_end {
+                let offset = logical_start - region_logical_start;
+                let chunk_len = remaining_len.min(region_size - offset);
+                self.fields.push(FieldRange {
+                    start: region.offset_in_firmware() + offset,
+                    len: chunk_len,
+                    field_path: field_path.clone(),
+                });
+                logical_start += chunk_len;
+                remaining_len -= chunk_len;
+            }
+            region_logical_start = region_logical_end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field_map::{
+        FieldMapBuilder, FieldRange, MEMORY_CLOCK_TABLE_BASE_ENTRY_FIELDS,
+        MEMORY_CLOCK_TABLE_HEADER_FIELDS, MEMORY_CLOCK_TABLE_STRAP_ENTRY_FIELDS,
+        MEMORY_TWEAK_TABLE_BASE_ENTRY_BIT_FIELDS, MEMORY_TWEAK_TABLE_HEADER_FIELDS,
+        MEMORY_TWEAK_TABLE_RESERVED_0_SIZE, MEMORY_TWEAK_TABLE_RESERVED_1_SIZE,
+    };
+    use crate::nvidia::bit::perf::{
+        MemoryClockTableBaseEntry, MemoryClockTableHeader, MemoryClockTableStrapEntry,
+        MemoryTweakTableBaseEntry, MemoryTweakTableHeader,
+    };
+    use crate::FirmwareRegion;
+    use binread::{BinRead, BinReaderExt};
+    use serde::Serialize;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[derive(Debug)]
+    struct TestRegion {
+        offset: u64,
+        size: u64,
+    }
+
+    impl FirmwareRegion for TestRegion {
+        fn offset_in_firmware(&self) -> u64 {
+            self.offset
+        }
+
+        fn region_size(&self) -> u64 {
+            self.size
+        }
+    }
+
+    /// Parses `bytes` and checks that every mapped field holds the bytes at its mapped range
+    /// and that nothing is left for the unknown tail.
+    fn assert_byte_fields<T, A>(fields: &[(&str, u64)], bytes: &[u8], args: A)
+    where
+        T: BinRead<Args = A> + Serialize,
+    {
+        let value =
+            serde_json::to_value(Cursor::new(bytes).read_le_args::<T>(args).unwrap()).unwrap();
+        let mut start = 0;
+        for (name, size) in fields {
+            let field_bytes = &bytes[start..start + *size as usize];
+            let expected = if value[name].is_array() {
+                json!(field_bytes)
+            } else {
+                json!(field_bytes
+                    .iter()
+                    .rev()
+                    .fold(0u64, |acc, b| acc << 8 | *b as u64))
+            };
+            assert_eq!(expected, value[name], "{}", name);
+            start += *size as usize;
+        }
+        assert_eq!(bytes.len(), start);
+        assert_eq!(json!([]), value["unknown"]);
+    }
+
+    #[test]
+    fn test_memory_table_byte_fields() {
+        let sequence: Vec<u8> = (1..=11).collect();
+        assert_byte_fields::<MemoryClockTableHeader, _>(
+            MEMORY_CLOCK_TABLE_HEADER_FIELDS,
+            &[0x20, 6, 8, 11, 2, 3],
+            (),
+        );
+        assert_byte_fields::<MemoryClockTableBaseEntry, _>(
+            MEMORY_CLOCK_TABLE_BASE_ENTRY_FIELDS,
+            &sequence[..8],
+            (8,),
+        );
+        assert_byte_fields::<MemoryClockTableStrapEntry, _>(
+            MEMORY_CLOCK_TABLE_STRAP_ENTRY_FIELDS,
+            &sequence,
+            (11,),
+        );
+        assert_byte_fields::<MemoryTweakTableHeader, _>(
+            MEMORY_TWEAK_TABLE_HEADER_FIELDS,
+            &[0x20, 6, 76, 12, 1, 2],
+            (),
+        );
+    }
+
+    macro_rules! bit_sizes {
+        ($config:expr, $($field:ident),+) => {
+            vec![$((stringify!($field), $config.$field().count_ones() as u64)),+]
+        };
+    }
+
+    #[test]
+    fn test_memory_tweak_bit_fields() {
+        let entry: MemoryTweakTableBaseEntry = Cursor::new([0xFFu8; 76]).read_le().unwrap();
+        let expected = vec![
+            (
+                "config_0",
+                bit_sizes!(entry.config_0, rc, rfc, ras, rp, reserved_0),
+            ),
+            (
+                "config_1",
+                bit_sizes!(entry.config_1, cl, wl, rd_rcd, wr_rcd, reserved_1),
+            ),
+            (
+                "config_2",
+                bit_sizes!(
+                    entry.config_2,
+                    rpre,
+                    wpre,
+                    cdlr,
+                    reserved_3,
+                    wr,
+                    reserved_4,
+                    w2r_bus,
+                    r2w_bus
+                ),
+            ),
+            (
+                "config_3",
+                bit_sizes!(entry.config_3, pdex, pden2pdex, faw, aond, ccdl, ccds),
+            ),
+            (
+                "config_4",
+                bit_sizes!(
+                    entry.config_4,
+                    refresh_lo,
+                    refresh,
+                    rrd,
+                    delay_0,
+                    reserved_5
+                ),
+            ),
+            (
+                "config_5",
+                bit_sizes!(
+                    entry.config_5,
+                    adr_min,
+                    reserved_6,
+                    wrcrc,
+                    reserved_7,
+                    offset_0,
+                    delay_0_msb,
+                    offset_1,
+                    offset_2,
+                    delay_0_1
+                ),
+            ),
+            ("reserved_0", vec![]),
+            (
+                "voltage_config",
+                bit_sizes!(
+                    entry.voltage_config,
+                    drive_strength,
+                    voltage_0,
+                    voltage_1,
+                    voltage_2,
+                    r2p,
+                    voltage_3,
+                    reserved_0,
+                    voltage_4,
+                    reserved_1,
+                    voltage_5,
+                    reserved_2,
+                    rdcrc,
+                    reserved_3
+                ),
+            ),
+            (
+                "timing_config",
+                bit_sizes!(entry.timing_config, rfcsba, rfcsbr, reserved),
+            ),
+            ("reserved_1", vec![]),
+        ];
+        let fields: Vec<(&str, Vec<(&str, u64)>)> = MEMORY_TWEAK_TABLE_BASE_ENTRY_BIT_FIELDS
+            .iter()
+            .map(|(name, bit_fields)| (*name, bit_fields.to_vec()))
+            .collect();
+        assert_eq!(expected, fields);
+        assert_eq!(
+            entry.reserved_0.len() as u64,
+            MEMORY_TWEAK_TABLE_RESERVED_0_SIZE
+        );
+        assert_eq!(
+            entry.reserved_1.len() as u64,
+            MEMORY_TWEAK_TABLE_RESERVED_1_SIZE
+        );
+    }
+
+    #[test]
+    fn test_memory_tweak_base_entry_size() {
+        let bits: u64 = MEMORY_TWEAK_TABLE_BASE_ENTRY_BIT_FIELDS
+            .iter()
+            .flat_map(|(_, fields)| fields.iter().map(|(_, bits)| bits))
+            .sum();
+        assert_eq!(
+            76,
+            bits / 8 + MEMORY_TWEAK_TABLE_RESERVED_0_SIZE + MEMORY_TWEAK_TABLE_RESERVED_1_SIZE
+        );
+    }
+
+    #[test]
+    fn test_field_crossing_regions() {
+        let first = TestRegion {
+            offset: 0x100,
+            size: 0x10,
+        };
+        let second = TestRegion {
+            offset: 0x400,
+            size: 0x10,
+        };
+        let mut fields = Vec::new();
+        let mut builder = FieldMapBuilder {
+            regions: vec![&first as &dyn FirmwareRegion, &second],
+            fields: &mut fields,
+        };
+        builder.add_field(0x0E, 4, "field".to_string());
+        builder.add_bit_fields(0x12, "bits", &[("a", 3), ("b", 7), ("c", 6)]);
+
+        assert_eq!(
+            vec![
+                FieldRange {
+                    start: 0x10E,
+                    len: 2,
+                    field_path: "field".to_string()
+                },
+                FieldRange {
+                    start: 0x400,
+                    len: 2,
+                    field_path: "field".to_string()
+                },
+                FieldRange {
+                    start: 0x402,
+                    len: 1,
+                    field_path: "bits.a".to_string()
+                },
+                FieldRange {
+                    start: 0x402,
+                    len: 2,
+                    field_path: "bits.b".to_string()
+                },
+                FieldRange {
+                    start: 0x403,
+                    len: 1,
+                    field_path: "bits.c".to_string()
+                },
+            ],
+            fields
+        );
+    }
+}
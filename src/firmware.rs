@@ -1,31 +1,61 @@
 // SPDX-License-Identifier: MIT
 
-use crate::cursor::ContinuousRegionReader;
+use crate::cursor::{BufferedSeekable, ContinuousRegionReader, OffsetReader};
+use crate::field_map::FieldMap;
+use crate::nvidia::bit::dfp::{flat_panel_edid, Edid, EstablishedTimings, FlatPanelTable};
+use crate::nvidia::bit::mxm::{MxmAuxToCcbTable, MxmDigitalConnectorTable};
+use crate::nvidia::bit::nvinit::{ConditionTable, InitFunctionTable, MacroIndexTable, MacroTable};
 use crate::nvidia::bit::nvlink::NvLinkConfigData;
 use crate::nvidia::bit::perf::{
-    MemoryClockTable, MemoryTweakTable, PowerPolicyTable, VirtualPStateTable20,
+    MemoryClockTable, MemoryTweakTable, PowerPolicyTable, RawTable, ThermalChannelTable,
+    ThermalPolicyTable, VirtualPStateTable20, VoltageSummary,
+};
+use crate::nvidia::bit::{
+    BITStructure, BITToken, BITTokenType, DataRangeTable, PllInfo, PostCapabilities, StringToken,
 };
-use crate::nvidia::bit::{BITStructure, BITTokenType, PllInfo, StringToken};
 use crate::nvidia::dcb::{
-    CommunicationsControlBlock, ConnectorTable, DeviceControlBlock, GpioAssignmentTable,
-    I2cDevicesTable,
+    CommunicationsControlBlock, ConnectorTable, DeviceControlBlock, DisplayCapabilities,
+    GpioAssignmentTable, I2cDevicesTable,
+};
+use crate::nvidia::nbsi::{NbsiPciExpansionRom, NbsiPlatInfo};
+use crate::nvidia::{
+    NvgiRegion, NvidiaPciDataExtended, NvidiaPciDataExtendedFlags, NvidiaPciExpansionRom,
+    RfrdRegion,
 };
-use crate::nvidia::nbsi::NbsiPciExpansionRom;
-use crate::nvidia::{NvgiRegion, NvidiaPciExpansionRom, RfrdRegion};
-use crate::pci_efi::EfiPciExpansionRom;
+use crate::pci_efi::{EfiPciExpansionRom, EfiPciExpansionRomMachineType};
 use crate::pci_legacy::PciExpansionRom;
-use crate::{FirmwareRegion, Region, RegionIterator, RegionStructure, RegionStructureIterator};
-use binread::BinReaderExt;
-use log::warn;
+use crate::{
+    default_region_parsers, has_region_signature, read_available, FirmwareRegion, ParseOptions,
+    Region, RegionIterator, RegionStructure, RegionStructureIterator,
+};
+use binread::{BinRead, BinReaderExt};
+use log::{trace, warn};
 use serde::Serialize;
-use std::io::{Read, Seek, SeekFrom};
-use std::mem;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::{iter, mem};
+
+/// Longest non-ROM header that is stripped from the start of the file
+const MAX_PREAMBLE_LEN: u64 = 64 * 1024;
 
 #[derive(Default, Debug, Serialize)]
 pub struct FirmwareBundleInfo {
     pub firmwares: Vec<FirmwareInfo>,
 
     pub nbsi_pci_expansion_rom: Option<NbsiPciExpansionRom>,
+
+    /// Parsed regions in file order, only kept with `ParseOptions::retain_regions`
+    #[serde(skip)]
+    pub regions: Vec<Region>,
+    /// Index in `firmwares` of each retained region, `None` for regions shared by the bundle
+    #[serde(skip)]
+    pub region_firmware_indexes: Vec<Option<usize>>,
+
+    /// Length of the non-ROM header in front of the first region, e.g. of nvflash backups.
+    /// Region offsets are relative to the end of the header.
+    pub preamble_len: u64,
+
+    /// Parse warnings, only kept with `ParseOptions::collect_warnings`
+    pub warnings: Vec<String>,
 }
 
 #[derive(Default, Debug, Serialize)]
@@ -34,7 +64,136 @@ pub struct FirmwareInfo {
     pub rfrd_region: Option<RfrdRegion>,
     pub legacy_pci_image: Option<LegacyPciImageInfo>,
     pub efi_pci_image: Option<EfiPciExpansionRom>,
+    /// EFI images after the first one, e.g. one per architecture
+    pub additional_efi_pci_images: Vec<EfiPciExpansionRom>,
+    pub open_firmware_images: Vec<PciExpansionRom>,
     pub nv_pci_expansion_roms: Vec<NvidiaPciExpansionRom>,
+    pub hdcp_images: Vec<NvidiaPciExpansionRom>,
+    /// Index in [`FirmwareInfo::pci_images`] of the image referenced by the RFRD region
+    pub rfrd_image_index: Option<usize>,
+}
+
+impl FirmwareInfo {
+    /// PCI images of the firmware, the legacy image goes first, then EFI and NVIDIA images
+    pub fn pci_images(&self) -> Vec<&dyn FirmwareRegion> {
+        let mut images: Vec<&dyn FirmwareRegion> = Vec::new();
+        if let Some(legacy) = &self.legacy_pci_image {
+            images.push(&legacy.image);
+        }
+        for efi in self.efi_pci_images() {
+            images.push(efi);
+        }
+        for open_firmware in &self.open_firmware_images {
+            images.push(open_firmware);
+        }
+        for nv in &self.nv_pci_expansion_roms {
+            images.push(nv);
+        }
+        for hdcp in &self.hdcp_images {
+            images.push(hdcp);
+        }
+        images
+    }
+
+    /// NVGI and RFRD regions followed by the PCI images of the firmware
+    pub fn regions(&self) -> Vec<&dyn FirmwareRegion> {
+        let mut regions: Vec<&dyn FirmwareRegion> = Vec::new();
+        for nvgi in &self.nvgi_regions {
+            regions.push(nvgi);
+        }
+        if let Some(rfrd) = &self.rfrd_region {
+            regions.push(rfrd);
+        }
+        regions.extend(self.pci_images());
+        regions
+    }
+
+    /// Drops the raw bytes of the PCI images, see `ParseOptions::capture_payloads`
+    pub fn drop_payloads(&mut self) {
+        if let Some(legacy) = &mut self.legacy_pci_image {
+            legacy.image.data = Vec::new();
+        }
+        for efi in self
+            .efi_pci_image
+            .iter_mut()
+            .chain(&mut self.additional_efi_pci_images)
+        {
+            efi.data = Vec::new();
+        }
+        for image in &mut self.open_firmware_images {
+            image.data = Vec::new();
+        }
+        for nv in self
+            .nv_pci_expansion_roms
+            .iter_mut()
+            .chain(&mut self.hdcp_images)
+        {
+            nv.data = Vec::new();
+        }
+    }
+
+    /// EFI images of the firmware, `efi_pci_image` goes first
+    pub fn efi_pci_images(&self) -> impl Iterator<Item = &EfiPciExpansionRom> {
+        self.efi_pci_image
+            .iter()
+            .chain(&self.additional_efi_pci_images)
+    }
+
+    fn add_efi_pci_image(&mut self, efi: EfiPciExpansionRom) {
+        if self.efi_pci_image.is_none() {
+            self.efi_pci_image = Some(efi);
+        } else {
+            self.additional_efi_pci_images.push(efi);
+        }
+    }
+
+    /// Whether the firmware has an NVGI or RFRD container
+    pub fn has_ifr_container(&self) -> bool {
+        !self.nvgi_regions.is_empty() || self.rfrd_region.is_some()
+    }
+
+    /// Legacy image followed by the NVIDIA images its structures may point into. The pointers are
+    /// relative to the start of the legacy image, so the concatenated bytes parse on their own
+    /// even when other images sat between them in the firmware file. `source` is the file the
+    /// firmware was parsed from, past any preamble.
+    pub fn standalone_legacy_image<S: Read + Seek>(
+        &self,
+        source: &mut S,
+    ) -> crate::Result<Option<Vec<u8>>> {
+        let Some(info) = &self.legacy_pci_image else {
+            return Ok(None);
+        };
+        let mut regions: Vec<&dyn FirmwareRegion> = vec![&info.image];
+        for nv in &self.nv_pci_expansion_roms {
+            regions.push(nv);
+        }
+        let size: u64 = regions.iter().map(|region| region.region_size()).sum();
+        let mut reader = ContinuousRegionReader::try_new(source, regions)?;
+        reader.seek(SeekFrom::Start(0))?;
+        let mut bytes = vec![0u8; size as usize];
+        reader.read_exact(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+
+    /// The PCI image referenced by the RFRD region
+    pub fn rfrd_image(&self) -> Option<&dyn FirmwareRegion> {
+        self.rfrd_image_index
+            .and_then(|index| self.pci_images().get(index).copied())
+    }
+
+    /// Extended PCI data headers of the images that have one, the legacy image goes first
+    pub fn data_headers_extended(&self) -> impl Iterator<Item = &NvidiaPciDataExtended> {
+        self.legacy_pci_image
+            .iter()
+            .map(|image| &image.image.data_header_extended)
+            .chain(self.efi_pci_images().map(|efi| &efi.data_header_extended))
+            .chain(
+                self.nv_pci_expansion_roms
+                    .iter()
+                    .map(|nv| &nv.data_header_extended),
+            )
+            .flatten()
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -43,14 +202,34 @@ pub struct LegacyPciImageInfo {
 
     // BIT
     pub bit_table_structure: Option<BITStructure>,
+    /// Parsed BIT tokens ordered by token id
     pub bit_tokens_data: Vec<BITTokenType>,
+    /// Data versions of the tokens in `bit_tokens_data`, in the same order
+    pub bit_tokens_versions: Vec<u8>,
     pub bit_string_token: Option<StringToken>,
+    /// Data range table of the BIOS token
+    pub data_range_table: Option<DataRangeTable>,
     pub nvlink_config_data: Option<NvLinkConfigData>,
+    pub macro_index_table: Option<MacroIndexTable>,
+    pub macro_table: Option<MacroTable>,
+    pub condition_table: Option<ConditionTable>,
+    pub init_function_table: Option<InitFunctionTable>,
     pub memory_clock_table: Option<MemoryClockTable>,
     pub memory_tweak_table: Option<MemoryTweakTable>,
     pub pll_info: Option<PllInfo>,
     pub power_policy_table: Option<PowerPolicyTable>,
     pub virtual_p_state_table: Option<VirtualPStateTable20>,
+    pub thermal_channel_table: Option<ThermalChannelTable>,
+    pub thermal_policy_table: Option<ThermalPolicyTable>,
+    pub didt_table: Option<RawTable>,
+    pub ventura_table: Option<RawTable>,
+    pub flat_panel_table: Option<FlatPanelTable>,
+    pub flat_panel_established_timings: Option<EstablishedTimings>,
+    /// EDID block embedded in the image, resolved from the flat panel established timings
+    /// pointer. [`crate::nvidia::bit::dfp::find_edids`] scans the image data for other blocks.
+    pub embedded_edid: Option<Edid>,
+    pub mxm_digital_connector_table: Option<MxmDigitalConnectorTable>,
+    pub mxm_aux_to_ccb_table: Option<MxmAuxToCcbTable>,
 
     // DCB
     pub device_control_block: Option<DeviceControlBlock>,
@@ -58,38 +237,197 @@ pub struct LegacyPciImageInfo {
     pub i2c_devices_table: Option<I2cDevicesTable>,
     pub connector_table: Option<ConnectorTable>,
     pub communications_control_block: Option<CommunicationsControlBlock>,
+
+    // Structures found after the first BIT and DCB
+    pub additional_bit_structures: Vec<BITStructure>,
+    pub additional_device_control_blocks: Vec<DeviceControlBlock>,
+
+    /// Tables that are referenced by the BIT or DCB but failed to parse
+    pub table_failures: Vec<TableFailure>,
+}
+
+impl LegacyPciImageInfo {
+    /// Display outputs resolved from the device control block and connector table
+    pub fn display_capabilities(&self) -> DisplayCapabilities {
+        DisplayCapabilities::new(
+            self.device_control_block.as_ref(),
+            self.connector_table.as_ref(),
+        )
+    }
+
+    pub fn post_capabilities(&self) -> Option<PostCapabilities> {
+        self.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::Bios(bios) => Some(bios.post_capabilities()),
+            _ => None,
+        })
+    }
+
+    /// Memory voltages of every strap by memory clock, see [`MemoryTweakTable::strap_voltages`]
+    pub fn memory_voltages(&self) -> Vec<(u16, VoltageSummary)> {
+        match (&self.memory_clock_table, &self.memory_tweak_table) {
+            (Some(memory_clock_table), Some(memory_tweak_table)) => {
+                memory_tweak_table.strap_voltages(memory_clock_table)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Parse outcome of each optional table, named as in the parse warnings
+    pub fn table_statuses(&self) -> Vec<(&'static str, TableStatus)> {
+        OPTIONAL_TABLES
+            .into_iter()
+            .map(|(table, is_present)| {
+                let status = if is_present(self) {
+                    TableStatus::Present
+                } else if let Some(failure) = self.table_failures.iter().find(|f| f.table == table)
+                {
+                    TableStatus::Failed(failure.error.clone())
+                } else {
+                    TableStatus::Absent
+                };
+                (table, status)
+            })
+            .collect()
+    }
+
+    /// Parsed BIT tokens with their data versions
+    pub fn bit_tokens_with_versions(&self) -> impl Iterator<Item = (u8, &BITTokenType)> {
+        self.bit_tokens_versions
+            .iter()
+            .copied()
+            .zip(&self.bit_tokens_data)
+    }
+
+    fn new(image: PciExpansionRom) -> Self {
+        Self {
+            image,
+            bit_table_structure: None,
+            bit_tokens_data: vec![],
+            bit_tokens_versions: vec![],
+            bit_string_token: None,
+            data_range_table: None,
+            nvlink_config_data: None,
+            macro_index_table: None,
+            macro_table: None,
+            condition_table: None,
+            init_function_table: None,
+            memory_tweak_table: None,
+            memory_clock_table: None,
+            pll_info: None,
+            device_control_block: None,
+            gpio_assignment_table: None,
+            i2c_devices_table: None,
+            connector_table: None,
+            communications_control_block: None,
+            additional_bit_structures: vec![],
+            additional_device_control_blocks: vec![],
+            table_failures: vec![],
+            power_policy_table: None,
+            virtual_p_state_table: None,
+            thermal_channel_table: None,
+            thermal_policy_table: None,
+            didt_table: None,
+            ventura_table: None,
+            flat_panel_table: None,
+            flat_panel_established_timings: None,
+            embedded_edid: None,
+            mxm_digital_connector_table: None,
+            mxm_aux_to_ccb_table: None,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for FirmwareBundleInfo {
+    type Error = crate::Error;
+
+    fn try_from(bytes: &[u8]) -> crate::Result<Self> {
+        Self::parse_bytes(bytes)
+    }
+}
+
+impl TryFrom<Vec<u8>> for FirmwareBundleInfo {
+    type Error = crate::Error;
+
+    fn try_from(bytes: Vec<u8>) -> crate::Result<Self> {
+        Self::parse_bytes(&bytes)
+    }
 }
 
 impl FirmwareBundleInfo {
     pub fn parse<S: Read + Seek>(source: &mut S) -> crate::Result<Self> {
+        Self::parse_with_options(source, &ParseOptions::default())
+    }
+
+    pub fn parse_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        Self::parse(&mut Cursor::new(bytes))
+    }
+
+    /// Parses a stream without `Seek` support, buffering at most `max_size` bytes of it
+    pub fn parse_stream<R: Read>(source: R, max_size: u64) -> crate::Result<Self> {
+        Self::parse(&mut BufferedSeekable::new(source, max_size)?)
+    }
+
+    pub fn parse_with_options<S: Read + Seek>(
+        source: &mut S,
+        options: &ParseOptions,
+    ) -> crate::Result<Self> {
+        Ok(Self::parse_bundles(source, options, false)?
+            .pop()
+            .unwrap_or_default())
+    }
+
+    /// Parses a file with several card ROMs concatenated, one bundle per card ROM
+    pub fn parse_all<S: Read + Seek>(
+        source: &mut S,
+        options: &ParseOptions,
+    ) -> crate::Result<Vec<Self>> {
+        Self::parse_bundles(source, options, true)
+    }
+
+    fn parse_bundles<S: Read + Seek>(
+        source: &mut S,
+        options: &ParseOptions,
+        split_roms: bool,
+    ) -> crate::Result<Vec<Self>> {
+        let preamble_len = Self::detect_preamble_len(source, options)?;
+        let mut source = OffsetReader::new(source, preamble_len);
+        let source = &mut source;
+        let mut firmware_bundles: Vec<FirmwareBundleInfo> = Vec::new();
         let mut firmware_bundle = FirmwareBundleInfo::default();
         let mut firmware = FirmwareInfo::default();
         let mut firmwares: Vec<FirmwareInfo> = Vec::new();
-        let mut region_iterator = RegionIterator::new(source);
+        let mut region_iterator = RegionIterator::with_options(source, options.clone());
 
         while let Some(region) = region_iterator.try_next()? {
+            if split_roms && Self::starts_new_rom(&firmware_bundle, &firmware, &region) {
+                firmwares.push(mem::replace(&mut firmware, FirmwareInfo::default()));
+                firmware_bundle.firmwares = mem::replace(&mut firmwares, Vec::new());
+                firmware_bundles.push(mem::replace(
+                    &mut firmware_bundle,
+                    FirmwareBundleInfo::default(),
+                ));
+            }
+            if options.retain_regions {
+                firmware_bundle.regions.push(region.clone());
+            }
+            let in_firmware = !matches!(
+                region,
+                Region::NbsiPciExpansionRom(_) | Region::Unknown(_) | Region::Custom(_)
+            );
             match region {
                 Region::LegacyPciExpansionRom(legacy) => {
-                    firmware.legacy_pci_image.replace(LegacyPciImageInfo {
-                        image: legacy,
-                        bit_table_structure: None,
-                        bit_tokens_data: vec![],
-                        bit_string_token: None,
-                        nvlink_config_data: None,
-                        memory_tweak_table: None,
-                        memory_clock_table: None,
-                        pll_info: None,
-                        device_control_block: None,
-                        gpio_assignment_table: None,
-                        i2c_devices_table: None,
-                        connector_table: None,
-                        communications_control_block: None,
-                        power_policy_table: None,
-                        virtual_p_state_table: None,
-                    });
+                    firmware
+                        .legacy_pci_image
+                        .replace(LegacyPciImageInfo::new(legacy));
+                }
+                Region::OpenFirmwareImage(open_firmware) => {
+                    firmware.open_firmware_images.push(open_firmware);
+                }
+                Region::HdcpImage(hdcp) => {
+                    firmware.hdcp_images.push(hdcp);
                 }
                 Region::EfiPciExpansionRom(efi) => {
-                    firmware.efi_pci_image.replace(efi);
+                    firmware.add_efi_pci_image(efi);
                 }
                 Region::NvidiaPciExpansionRom(nv) => {
                     firmware.nv_pci_expansion_roms.push(nv);
@@ -106,16 +444,345 @@ impl FirmwareBundleInfo {
                 Region::RfrdRegion(rfrd) => {
                     firmware.rfrd_region.replace(rfrd);
                 }
+                Region::Unknown(unknown) => {
+                    trace!(
+                        "Unknown region at {} with signature {:02X?}",
+                        unknown.offset_in_firmware,
+                        unknown.signature
+                    );
+                }
+                Region::Custom(custom) => {
+                    trace!(
+                        "Custom {} region at {}",
+                        custom.kind,
+                        custom.offset_in_firmware
+                    );
+                }
+            }
+            if options.retain_regions {
+                // A NVGI region may close the previous firmware, so the index is taken after the match
+                firmware_bundle
+                    .region_firmware_indexes
+                    .push(in_firmware.then_some(firmwares.len()));
             }
         }
 
         firmwares.push(mem::replace(&mut firmware, FirmwareInfo::default()));
+        firmware_bundle.firmwares = firmwares;
+        firmware_bundles.push(firmware_bundle);
 
-        for firmware in &mut firmwares {
-            Self::parse_legacy_pci_image_info(source, firmware)?;
+        for firmware_bundle in &mut firmware_bundles {
+            let mut warnings = Vec::new();
+            for firmware in &mut firmware_bundle.firmwares {
+                Self::resolve_rfrd_image(source, firmware, options, &mut warnings)?;
+                Self::parse_legacy_pci_image_info(source, firmware, options, &mut warnings)?;
+                if !options.capture_payloads {
+                    firmware.drop_payloads();
+                }
+            }
+            if !options.capture_payloads {
+                firmware_bundle.regions.iter_mut().for_each(drop_payload);
+            }
+            firmware_bundle.preamble_len = preamble_len;
+            if options.collect_warnings {
+                firmware_bundle.warnings = warnings;
+            }
+        }
+        Ok(firmware_bundles)
+    }
+
+    /// Length of the preamble before the first region, see `FirmwareBundleInfo::preamble_len`.
+    /// Sources positioned past the start are scanned from their position as before, so they
+    /// have no preamble.
+    pub fn detect_preamble_len<S: Read + Seek>(
+        source: &mut S,
+        options: &ParseOptions,
+    ) -> crate::Result<u64> {
+        match source.stream_position()? {
+            0 => Self::scan_preamble_len(source, options),
+            _ => Ok(0),
+        }
+    }
+
+    /// Offset of the first region if the file doesn't start with a region signature
+    fn scan_preamble_len<S: Read + Seek>(
+        source: &mut S,
+        options: &ParseOptions,
+    ) -> crate::Result<u64> {
+        let mut buf = [0u8; 4];
+        source.rewind()?;
+        let len = read_available(source, &mut buf);
+        if len < buf.len() || has_region_signature(&default_region_parsers(), &buf) {
+            source.rewind()?;
+            return Ok(0);
+        }
+        source.rewind()?;
+        let preamble_len = match RegionIterator::with_options(source, options.clone()).try_next()? {
+            Some(region) if region.offset_in_firmware() <= MAX_PREAMBLE_LEN => {
+                region.offset_in_firmware()
+            }
+            _ => 0,
+        };
+        if preamble_len > 0 {
+            trace!("Skipping {} bytes of preamble", preamble_len);
+        }
+        source.seek(SeekFrom::Start(preamble_len))?;
+        Ok(preamble_len)
+    }
+
+    /// A card ROM ends with its NBSI image, and a firmware has a single legacy image, so a second
+    /// one can only belong to the next card ROM
+    fn starts_new_rom(
+        firmware_bundle: &FirmwareBundleInfo,
+        firmware: &FirmwareInfo,
+        region: &Region,
+    ) -> bool {
+        match region {
+            Region::LegacyPciExpansionRom(_) => {
+                firmware_bundle.nbsi_pci_expansion_rom.is_some()
+                    || firmware.legacy_pci_image.is_some()
+            }
+            Region::NvgiRegion(_) => firmware_bundle.nbsi_pci_expansion_rom.is_some(),
+            _ => false,
         }
-        firmware_bundle.firmwares = firmwares;
-        Ok(firmware_bundle)
+    }
+
+    /// Links the RFRD region to the PCI image it points at, reading the image if the scan missed it.
+    /// The base of `pci_rom_offset` is not confirmed, so both the firmware start and the RFRD
+    /// region are tried.
+    fn resolve_rfrd_image<S: Read + Seek>(
+        source: &mut S,
+        firmware: &mut FirmwareInfo,
+        options: &ParseOptions,
+        warnings: &mut Vec<String>,
+    ) -> crate::Result<()> {
+        let Some(rfrd) = &firmware.rfrd_region else {
+            return Ok(());
+        };
+        let pci_rom_offset = rfrd.header.pci_rom_offset as u64;
+        let candidates = [pci_rom_offset, rfrd.offset_in_firmware + pci_rom_offset];
+
+        for offset in candidates {
+            if let Some(index) = firmware
+                .pci_images()
+                .iter()
+                .position(|image| image.offset_in_firmware() == offset)
+            {
+                firmware.rfrd_image_index = Some(index);
+                return Ok(());
+            }
+        }
+        for offset in candidates {
+            let region = match Region::read_at(source, offset, options) {
+                Ok(region) => region.filter(|region| region.offset_in_firmware() == offset),
+                Err(err @ crate::Error::InvalidFormat(_)) => {
+                    add_warning(
+                        warnings,
+                        format!("PCI image referenced by RFRD at {:#X}: {}", offset, err),
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    trace!("No PCI image referenced by RFRD at {}: {}", offset, err);
+                    continue;
+                }
+            };
+            let added = match region {
+                Some(Region::LegacyPciExpansionRom(legacy))
+                    if firmware.legacy_pci_image.is_none() =>
+                {
+                    firmware
+                        .legacy_pci_image
+                        .replace(LegacyPciImageInfo::new(legacy));
+                    true
+                }
+                Some(Region::EfiPciExpansionRom(efi)) => {
+                    firmware.add_efi_pci_image(efi);
+                    true
+                }
+                Some(Region::NvidiaPciExpansionRom(nv)) => {
+                    firmware.nv_pci_expansion_roms.push(nv);
+                    true
+                }
+                _ => false,
+            };
+            if added {
+                firmware.rfrd_image_index = firmware
+                    .pci_images()
+                    .iter()
+                    .position(|image| image.offset_in_firmware() == offset);
+                return Ok(());
+            }
+        }
+        add_warning(
+            warnings,
+            format!(
+                "PCI image referenced by RFRD with offset {:#X} not found",
+                pci_rom_offset
+            ),
+        );
+        Ok(())
+    }
+
+    /// Invokes the callback for each region without keeping the parsed regions in memory.
+    pub fn parse_streaming<S: Read + Seek, F: FnMut(Region)>(
+        source: &mut S,
+        mut callback: F,
+    ) -> crate::Result<()> {
+        let preamble_len = Self::detect_preamble_len(source, &ParseOptions::default())?;
+        let mut source = OffsetReader::new(source, preamble_len);
+        let mut region_iterator = RegionIterator::new(&mut source);
+        while let Some(region) = region_iterator.try_next()? {
+            callback(region);
+        }
+        Ok(())
+    }
+
+    /// Index in `firmwares` of the firmware that contains the region, `None` for the NBSI image
+    pub fn firmware_index_of(&self, region: &dyn FirmwareRegion) -> Option<usize> {
+        self.firmwares.iter().position(|firmware| {
+            firmware
+                .regions()
+                .iter()
+                .any(|r| r.offset_in_firmware() == region.offset_in_firmware())
+        })
+    }
+
+    /// Retained regions with the index of their firmware, see `ParseOptions::retain_regions`
+    pub fn regions_with_firmware_index(&self) -> impl Iterator<Item = (&Region, Option<usize>)> {
+        self.regions
+            .iter()
+            .zip(self.region_firmware_indexes.iter().copied())
+    }
+
+    /// Retained regions sorted by their offset in the firmware file, see `ParseOptions::retain_regions`
+    pub fn regions_sorted(&self) -> Vec<&Region> {
+        let mut regions: Vec<&Region> = self.regions.iter().collect();
+        regions.sort();
+        regions
+    }
+
+    #[cfg(feature = "postcard")]
+    pub fn to_postcard(&self) -> crate::Result<Vec<u8>> {
+        Ok(postcard::to_allocvec(self)?)
+    }
+
+    /// The smallest region that contains the given file offset
+    pub fn region_at(&self, offset: u64) -> Option<&dyn FirmwareRegion> {
+        self.all_regions()
+            .into_iter()
+            .filter(|region| region.contains(offset))
+            .min_by_key(|region| region.region_size())
+    }
+
+    pub fn all_regions(&self) -> Vec<&dyn FirmwareRegion> {
+        let mut regions: Vec<&dyn FirmwareRegion> = self
+            .firmwares
+            .iter()
+            .flat_map(|firmware| firmware.regions())
+            .collect();
+        if let Some(nbsi) = &self.nbsi_pci_expansion_rom {
+            regions.push(nbsi);
+        }
+        regions.sort_by_key(|r| r.offset_in_firmware());
+        regions
+    }
+
+    /// Platform info of the NBSI image, the workaround copy is used if the primary one is missing
+    pub fn nbsi_plat_info(&self) -> Option<&NbsiPlatInfo> {
+        self.nbsi_pci_expansion_rom
+            .as_ref()
+            .and_then(|nbsi| nbsi.plat_info.as_ref().or(nbsi.plat_info_war.as_ref()))
+    }
+
+    pub fn field_map(&self) -> FieldMap {
+        FieldMap::new(self)
+    }
+
+    pub fn summary(&self) -> Summary {
+        let primary_data_header = self
+            .firmwares
+            .iter()
+            .find_map(|f| f.legacy_pci_image.as_ref())
+            .map(|image| &image.image.data_header);
+        let pci_image_count = self
+            .firmwares
+            .iter()
+            .map(|f| {
+                f.legacy_pci_image.iter().count()
+                    + f.efi_pci_images().count()
+                    + f.nv_pci_expansion_roms.len()
+            })
+            .sum();
+
+        Summary {
+            firmware_count: self.firmwares.len(),
+            pci_image_count,
+            nbsi_present: self.nbsi_pci_expansion_rom.is_some(),
+            vendor_id: primary_data_header.map(|h| h.vendor_id),
+            device_id: primary_data_header.map(|h| h.device_id),
+            vbios_version: self
+                .v_bios_info()
+                .into_iter()
+                .map(|info| info.version)
+                .find(|version| version != "N/A"),
+            parsed_extent: self
+                .all_regions()
+                .iter()
+                .map(|r| r.end_offset_in_firmware())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// Display outputs of the first legacy image with a device control block or connector table
+    pub fn display_outputs(&self) -> DisplayCapabilities {
+        self.firmwares
+            .iter()
+            .filter_map(|f| f.legacy_pci_image.as_ref())
+            .find(|image| image.device_control_block.is_some() || image.connector_table.is_some())
+            .map(|image| image.display_capabilities())
+            .unwrap_or_default()
+    }
+
+    /// Distinct machine types of the EFI images in firmware order
+    pub fn efi_architectures(&self) -> Vec<EfiPciExpansionRomMachineType> {
+        let mut architectures = Vec::new();
+        for machine_type in self
+            .firmwares
+            .iter()
+            .flat_map(|f| f.efi_pci_images())
+            .map(|image| image.machine_type())
+        {
+            if !architectures.contains(&machine_type) {
+                architectures.push(machine_type);
+            }
+        }
+        architectures
+    }
+
+    /// Whether any firmware has an NVGI or RFRD container, see
+    /// [`FirmwareInfo::has_ifr_container`]
+    pub fn has_ifr_container(&self) -> bool {
+        self.firmwares.iter().any(|f| f.has_ifr_container())
+    }
+
+    /// Whether private images are enabled or ROM packs are applied on top of the reference image
+    pub fn is_partner_customized(&self) -> bool {
+        self.firmwares.iter().any(|firmware| {
+            let private_images_enabled = firmware.data_headers_extended().any(|ext| {
+                ext.flags
+                    .contains(NvidiaPciDataExtendedFlags::PrivateImagesEnabled)
+            });
+            let rom_packs_applied = firmware
+                .legacy_pci_image
+                .iter()
+                .flat_map(|image| &image.bit_tokens_data)
+                .any(|token| {
+                    matches!(token, BITTokenType::Bios(bios) if bios.applied_rom_pack_count > 0)
+                });
+            private_images_enabled || rom_packs_applied
+        })
     }
 
     pub fn v_bios_info(&self) -> Vec<VBiosInfo> {
@@ -131,23 +798,22 @@ impl FirmwareBundleInfo {
                 if let Some(image) = &f.legacy_pci_image {
                     for bit_token in &image.bit_tokens_data {
                         if let BITTokenType::Bios(bios_token) = bit_token {
-                            info.version = format!(
-                                "{}.{:02X}",
-                                bios_token.bios_version, bios_token.bios_oem_version
-                            );
+                            info.version = bios_token.version();
                         }
                     }
-                    if let Some(ext) = &image.image.data_header_extended {
-                        info.gop_version = ext
-                            .gop_version
-                            .map(|v| v.non_zero().map(|v| v.to_string()))
-                            .flatten();
-                        info.subsystem_id = ext
-                            .subsystem_id
-                            .map(|v| v.non_zero().map(|v| v.to_string()))
-                            .flatten();
-                    }
                 }
+                info.gop_version = f
+                    .data_headers_extended()
+                    .find_map(|ext| ext.gop_version.as_ref().and_then(|v| v.non_zero()))
+                    .map(|v| v.to_string());
+                info.subsystem_id = f
+                    .data_headers_extended()
+                    .find_map(|ext| ext.subsystem_id.as_ref().and_then(|v| v.non_zero()))
+                    .or_else(|| {
+                        self.nbsi_plat_info()
+                            .and_then(|plat_info| plat_info.subsystem_id.non_zero())
+                    })
+                    .map(|v| v.to_string());
 
                 info
             })
@@ -157,6 +823,8 @@ impl FirmwareBundleInfo {
     fn parse_legacy_pci_image_info<S: Read + Seek>(
         source: &mut S,
         firmware: &mut FirmwareInfo,
+        options: &ParseOptions,
+        warnings: &mut Vec<String>,
     ) -> crate::Result<()> {
         if let Some(info) = firmware.legacy_pci_image.as_mut() {
             let mut legacy_image_regions: Vec<&dyn FirmwareRegion> = vec![&info.image];
@@ -166,13 +834,38 @@ impl FirmwareBundleInfo {
             }
             let mut legacy_image_reader = ContinuousRegionReader::new(source, legacy_image_regions);
             legacy_image_reader.seek(SeekFrom::Start(info.image.header.pcir_offset as u64))?;
-            let structures: Vec<RegionStructure> =
-                RegionStructureIterator::new(&mut legacy_image_reader).collect();
+            let mut structure_iterator =
+                RegionStructureIterator::with_options(&mut legacy_image_reader, options.clone());
+            let mut structures: Vec<RegionStructure> = if options.strict {
+                iter::from_fn(|| structure_iterator.try_next().transpose())
+                    .collect::<crate::Result<_>>()?
+            } else {
+                structure_iterator.collect()
+            };
+            for structure in &mut structures {
+                if let Some(region) =
+                    legacy_image_reader.logical_region(structure.offset_in_region())
+                {
+                    structure.set_region_offset_in_firmware(region.offset_in_firmware());
+                }
+            }
 
-            'structures_iteration: for structure in structures {
+            for structure in structures {
                 match structure {
                     RegionStructure::BiosInformationTable(bit) => {
-                        for token in &bit.tokens {
+                        if info.bit_table_structure.is_some() {
+                            add_warning(
+                                warnings,
+                                "Additional BIT structure found, its tokens are not parsed"
+                                    .to_string(),
+                            );
+                            info.additional_bit_structures.push(bit);
+                            continue;
+                        }
+                        // Sorted by id, equivalent ROMs with reordered tokens serialize the same
+                        let mut tokens: Vec<&BITToken> = bit.tokens.iter().collect();
+                        tokens.sort_by_key(|token| token.id);
+                        for token in tokens {
                             let bit_token_data = token.data(&mut legacy_image_reader);
                             match &bit_token_data {
                                 Ok(BITTokenType::String(ptrs)) => {
@@ -181,110 +874,447 @@ impl FirmwareBundleInfo {
                                     info.bit_string_token.replace(string_token);
                                 }
                                 Ok(BITTokenType::NvInit(ptrs)) => {
-                                    let nvlink_token = legacy_image_reader
-                                        .read_le_args::<NvLinkConfigData>((ptrs.clone(),))?;
-                                    info.nvlink_config_data.replace(nvlink_token);
+                                    info.nvlink_config_data = read_optional_table(
+                                        &mut legacy_image_reader,
+                                        (*ptrs,),
+                                        table_names::NVLINK_CONFIG_DATA,
+                                        &mut info.table_failures,
+                                    );
+                                    if ptrs.macro_index_table_ptr > 0 {
+                                        info.macro_index_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::MACRO_INDEX_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                    if ptrs.macro_table_ptr > 0 {
+                                        info.macro_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::MACRO_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                    if ptrs.condition_table_ptr > 0 {
+                                        info.condition_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::CONDITION_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                    if ptrs.init_function_table_ptr > 0 {
+                                        info.init_function_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::INIT_FUNCTION_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
                                 }
                                 Ok(BITTokenType::Clock(ptrs)) => {
-                                    let pll_token = legacy_image_reader
-                                        .read_le_args::<PllInfo>((ptrs.clone(),))?;
-                                    info.pll_info.replace(pll_token);
+                                    if ptrs.pll_info_table_ptr > 0 {
+                                        info.pll_info = read_optional_table::<_, PllInfo>(
+                                            &mut legacy_image_reader,
+                                            (ptrs.clone(),),
+                                            table_names::PLL_INFO,
+                                            &mut info.table_failures,
+                                        );
+                                    }
                                 }
                                 Ok(BITTokenType::Perf(ptrs)) => {
                                     if ptrs.memory_clock_table_ptr > 0 {
-                                        let memory_clock_table = legacy_image_reader
-                                            .read_le_args::<MemoryClockTable>(
-                                            (ptrs.clone(),),
-                                        )?;
-                                        info.memory_clock_table.replace(memory_clock_table);
+                                        info.memory_clock_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::MEMORY_CLOCK_TABLE,
+                                            &mut info.table_failures,
+                                        );
                                     }
 
                                     if ptrs.memory_tweak_table_ptr > 0 {
-                                        let memory_tweak_table = legacy_image_reader
-                                            .read_le_args::<MemoryTweakTable>(
-                                            (ptrs.clone(),),
-                                        )?;
-                                        info.memory_tweak_table.replace(memory_tweak_table);
+                                        info.memory_tweak_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::MEMORY_TWEAK_TABLE,
+                                            &mut info.table_failures,
+                                        );
                                     }
 
                                     if ptrs.virtual_p_state_table_ptr > 0 {
-                                        let virtual_p_state_table = legacy_image_reader
-                                            .read_le_args::<VirtualPStateTable20>(
-                                            (ptrs.clone(),),
-                                        )?;
-                                        info.virtual_p_state_table.replace(virtual_p_state_table);
+                                        info.virtual_p_state_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::VIRTUAL_P_STATE_TABLE,
+                                            &mut info.table_failures,
+                                        );
                                     }
 
                                     if ptrs.power_policy_table_ptr > 0 {
-                                        let power_policy_table = legacy_image_reader
-                                            .read_le_args::<PowerPolicyTable>(
-                                            (ptrs.clone(),),
-                                        )?;
-                                        info.power_policy_table.replace(power_policy_table);
+                                        info.power_policy_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::POWER_POLICY_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+
+                                    if ptrs.thermal_channel_table_ptr > 0 {
+                                        info.thermal_channel_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::THERMAL_CHANNEL_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+
+                                    if ptrs.thermal_policy_table_ptr > 0 {
+                                        info.thermal_policy_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::THERMAL_POLICY_TABLE,
+                                            &mut info.table_failures,
+                                        );
                                     }
+
+                                    if ptrs.didt_table_ptr > 0 {
+                                        info.didt_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (ptrs.didt_table_ptr as u64, table_names::DIDT_TABLE),
+                                            table_names::DIDT_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+
+                                    if ptrs.ventura_table_ptr > 0 {
+                                        info.ventura_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (
+                                                ptrs.ventura_table_ptr as u64,
+                                                table_names::VENTURA_TABLE,
+                                            ),
+                                            table_names::VENTURA_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                }
+                                Ok(BITTokenType::Bios(bios)) => {
+                                    if bios.data_range_table_pointer > 0 {
+                                        legacy_image_reader.seek(SeekFrom::Start(
+                                            bios.data_range_table_pointer as u64,
+                                        ))?;
+                                        info.data_range_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (),
+                                            table_names::DATA_RANGE_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                }
+                                Ok(BITTokenType::Dfp(ptrs)) => {
+                                    if ptrs.fp_table_ptr > 0 {
+                                        info.flat_panel_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (*ptrs,),
+                                            table_names::FLAT_PANEL_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+
+                                    if ptrs.fp_established_ptr > 0 {
+                                        legacy_image_reader.seek(SeekFrom::Start(
+                                            ptrs.fp_established_ptr as u64,
+                                        ))?;
+                                        info.flat_panel_established_timings = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (),
+                                            table_names::FLAT_PANEL_ESTABLISHED_TIMINGS,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                    info.embedded_edid = flat_panel_edid(&info.image.data, ptrs);
+                                }
+                                Ok(BITTokenType::Mxm(mxm)) if mxm.is_valid_mxm() => {
+                                    if mxm.mxm_digital_connector_table_ptr > 0 {
+                                        info.mxm_digital_connector_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (mxm.mxm_digital_connector_table_ptr,),
+                                            table_names::MXM_DIGITAL_CONNECTOR_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+
+                                    if mxm.mxm_aux_to_ccb_table_ptr > 0 {
+                                        info.mxm_aux_to_ccb_table = read_optional_table(
+                                            &mut legacy_image_reader,
+                                            (mxm.mxm_aux_to_ccb_table_ptr,),
+                                            table_names::MXM_AUX_TO_CCB_TABLE,
+                                            &mut info.table_failures,
+                                        );
+                                    }
+                                }
+                                Err(err) if options.strict => {
+                                    return Err(crate::Error::InvalidFormat(format!(
+                                        "Failed to read token {:#04X}: {}",
+                                        token.id, err
+                                    )));
                                 }
                                 Err(err) => {
-                                    warn!("Failed to read token {:?}, error: {:?}", token, err);
+                                    add_warning(
+                                        warnings,
+                                        format!(
+                                            "Failed to read token {:?}, error: {:?}",
+                                            token, err
+                                        ),
+                                    );
                                 }
                                 _ => {}
                             }
                             if let Ok(bit_token_data) = bit_token_data {
                                 info.bit_tokens_data.push(bit_token_data);
+                                info.bit_tokens_versions.push(token.data_version);
                             }
                         }
 
                         info.bit_table_structure.replace(bit);
                     }
                     RegionStructure::DeviceControlBlock(dcb) => {
+                        if info.device_control_block.is_some() {
+                            add_warning(
+                                warnings,
+                                "Additional DCB found, its tables are not parsed".to_string(),
+                            );
+                            info.additional_device_control_blocks.push(dcb);
+                            continue;
+                        }
                         if dcb.header.gpio_assignment_table_pointer > 0 {
                             legacy_image_reader.seek(SeekFrom::Start(
                                 dcb.header.gpio_assignment_table_pointer as u64,
                             ))?;
-                            let gpio_assignment_table =
-                                legacy_image_reader.read_le::<GpioAssignmentTable>()?;
-                            info.gpio_assignment_table.replace(gpio_assignment_table);
+                            info.gpio_assignment_table = read_optional_table(
+                                &mut legacy_image_reader,
+                                (),
+                                table_names::GPIO_ASSIGNMENT_TABLE,
+                                &mut info.table_failures,
+                            );
                         }
 
                         if dcb.header.i2c_devices_table_pointer > 0 {
                             legacy_image_reader.seek(SeekFrom::Start(
                                 dcb.header.i2c_devices_table_pointer as u64,
                             ))?;
-                            let i2c_devices_table =
-                                legacy_image_reader.read_le::<I2cDevicesTable>()?;
-                            info.i2c_devices_table.replace(i2c_devices_table);
+                            info.i2c_devices_table = read_optional_table(
+                                &mut legacy_image_reader,
+                                (),
+                                table_names::I2C_DEVICES_TABLE,
+                                &mut info.table_failures,
+                            );
                         }
 
                         if dcb.header.connector_table_pointer > 0 {
                             legacy_image_reader
                                 .seek(SeekFrom::Start(dcb.header.connector_table_pointer as u64))?;
-                            let connector_table =
-                                legacy_image_reader.read_le::<ConnectorTable>()?;
-                            info.connector_table.replace(connector_table);
+                            info.connector_table = read_optional_table(
+                                &mut legacy_image_reader,
+                                (),
+                                table_names::CONNECTOR_TABLE,
+                                &mut info.table_failures,
+                            );
                         }
 
                         if dcb.header.communications_control_block_pointer > 0 {
                             legacy_image_reader.seek(SeekFrom::Start(
                                 dcb.header.communications_control_block_pointer as u64,
                             ))?;
-                            let communications_control_block =
-                                legacy_image_reader.read_le::<CommunicationsControlBlock>()?;
-                            info.communications_control_block
-                                .replace(communications_control_block);
+                            info.communications_control_block = read_optional_table(
+                                &mut legacy_image_reader,
+                                (),
+                                table_names::COMMUNICATIONS_CONTROL_BLOCK,
+                                &mut info.table_failures,
+                            );
                         }
 
                         info.device_control_block.replace(dcb);
-
-                        break 'structures_iteration; // last parsed structure
                     }
                 }
             }
+
+            if let Some(failure) = info.table_failures.first() {
+                if options.strict {
+                    return Err(crate::Error::InvalidFormat(format!(
+                        "Failed to read {}: {}",
+                        failure.table, failure.error
+                    )));
+                }
+            }
+            warnings.extend(
+                info.table_failures
+                    .iter()
+                    .map(|failure| format!("Failed to read {}: {}", failure.table, failure.error)),
+            );
         }
 
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+fn add_warning(warnings: &mut Vec<String>, message: String) {
+    warn!("{}", message);
+    warnings.push(message);
+}
+
+fn drop_payload(region: &mut Region) {
+    match region {
+        Region::LegacyPciExpansionRom(image) | Region::OpenFirmwareImage(image) => {
+            image.data = Vec::new()
+        }
+        Region::EfiPciExpansionRom(efi) => efi.data = Vec::new(),
+        Region::NvidiaPciExpansionRom(nv) | Region::HdcpImage(nv) => nv.data = Vec::new(),
+        _ => {}
+    }
+}
+
+/// Names of the optional tables, shared by the parse warnings and `table_statuses`
+mod table_names {
+    pub const NVLINK_CONFIG_DATA: &str = "NVLink config data";
+    pub const MACRO_INDEX_TABLE: &str = "macro index table";
+    pub const MACRO_TABLE: &str = "macro table";
+    pub const CONDITION_TABLE: &str = "condition table";
+    pub const INIT_FUNCTION_TABLE: &str = "init function table";
+    pub const PLL_INFO: &str = "PLL info";
+    pub const MEMORY_CLOCK_TABLE: &str = "memory clock table";
+    pub const MEMORY_TWEAK_TABLE: &str = "memory tweak table";
+    pub const VIRTUAL_P_STATE_TABLE: &str = "virtual P-state table";
+    pub const POWER_POLICY_TABLE: &str = "power policy table";
+    pub const THERMAL_CHANNEL_TABLE: &str = "thermal channel table";
+    pub const THERMAL_POLICY_TABLE: &str = "thermal policy table";
+    pub const DIDT_TABLE: &str = "DIDT table";
+    pub const VENTURA_TABLE: &str = "ventura table";
+    pub const DATA_RANGE_TABLE: &str = "data range table";
+    pub const FLAT_PANEL_TABLE: &str = "flat panel table";
+    pub const FLAT_PANEL_ESTABLISHED_TIMINGS: &str = "flat panel established timings";
+    pub const MXM_DIGITAL_CONNECTOR_TABLE: &str = "MXM digital connector table";
+    pub const MXM_AUX_TO_CCB_TABLE: &str = "MXM AUX to CCB table";
+    pub const GPIO_ASSIGNMENT_TABLE: &str = "GPIO assignment table";
+    pub const I2C_DEVICES_TABLE: &str = "I2C devices table";
+    pub const CONNECTOR_TABLE: &str = "connector table";
+    pub const COMMUNICATIONS_CONTROL_BLOCK: &str = "communications control block";
+}
+
+/// Optional tables of a legacy image with an accessor telling whether each was decoded
+const OPTIONAL_TABLES: [(&str, fn(&LegacyPciImageInfo) -> bool); 23] = [
+    (table_names::NVLINK_CONFIG_DATA, |info| {
+        info.nvlink_config_data.is_some()
+    }),
+    (table_names::MACRO_INDEX_TABLE, |info| {
+        info.macro_index_table.is_some()
+    }),
+    (table_names::MACRO_TABLE, |info| info.macro_table.is_some()),
+    (table_names::CONDITION_TABLE, |info| {
+        info.condition_table.is_some()
+    }),
+    (table_names::INIT_FUNCTION_TABLE, |info| {
+        info.init_function_table.is_some()
+    }),
+    (table_names::PLL_INFO, |info| info.pll_info.is_some()),
+    (table_names::MEMORY_CLOCK_TABLE, |info| {
+        info.memory_clock_table.is_some()
+    }),
+    (table_names::MEMORY_TWEAK_TABLE, |info| {
+        info.memory_tweak_table.is_some()
+    }),
+    (table_names::VIRTUAL_P_STATE_TABLE, |info| {
+        info.virtual_p_state_table.is_some()
+    }),
+    (table_names::POWER_POLICY_TABLE, |info| {
+        info.power_policy_table.is_some()
+    }),
+    (table_names::THERMAL_CHANNEL_TABLE, |info| {
+        info.thermal_channel_table.is_some()
+    }),
+    (table_names::THERMAL_POLICY_TABLE, |info| {
+        info.thermal_policy_table.is_some()
+    }),
+    (table_names::DIDT_TABLE, |info| info.didt_table.is_some()),
+    (table_names::VENTURA_TABLE, |info| {
+        info.ventura_table.is_some()
+    }),
+    (table_names::DATA_RANGE_TABLE, |info| {
+        info.data_range_table.is_some()
+    }),
+    (table_names::FLAT_PANEL_TABLE, |info| {
+        info.flat_panel_table.is_some()
+    }),
+    (table_names::FLAT_PANEL_ESTABLISHED_TIMINGS, |info| {
+        info.flat_panel_established_timings.is_some()
+    }),
+    (table_names::MXM_DIGITAL_CONNECTOR_TABLE, |info| {
+        info.mxm_digital_connector_table.is_some()
+    }),
+    (table_names::MXM_AUX_TO_CCB_TABLE, |info| {
+        info.mxm_aux_to_ccb_table.is_some()
+    }),
+    (table_names::GPIO_ASSIGNMENT_TABLE, |info| {
+        info.gpio_assignment_table.is_some()
+    }),
+    (table_names::I2C_DEVICES_TABLE, |info| {
+        info.i2c_devices_table.is_some()
+    }),
+    (table_names::CONNECTOR_TABLE, |info| {
+        info.connector_table.is_some()
+    }),
+    (table_names::COMMUNICATIONS_CONTROL_BLOCK, |info| {
+        info.communications_control_block.is_some()
+    }),
+];
+
+fn read_optional_table<S: Read + Seek, T: BinRead>(
+    reader: &mut S,
+    args: T::Args,
+    name: &'static str,
+    failures: &mut Vec<TableFailure>,
+) -> Option<T> {
+    match reader.read_le_args::<T>(args) {
+        Ok(table) => Some(table),
+        Err(err) => {
+            warn!("Failed to read {}, error: {:?}", name, err);
+            failures.push(TableFailure {
+                table: name,
+                error: err.to_string(),
+            });
+            None
+        }
+    }
+}
+
+/// Table that is referenced by the image but failed to parse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableFailure {
+    pub table: &'static str,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TableStatus {
+    Present,
+    Absent,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub firmware_count: usize,
+    pub pci_image_count: usize,
+    pub nbsi_present: bool,
+    pub vendor_id: Option<u16>,
+    pub device_id: Option<u16>,
+    pub vbios_version: Option<String>,
+    /// End offset of the last parsed region
+    pub parsed_extent: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VBiosInfo {
     pub version: String,
     pub gop_version: Option<String>,
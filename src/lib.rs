@@ -6,17 +6,30 @@ use binread::{BinRead, BinReaderExt, BinResult, ReadOptions};
 use log::trace;
 use serde::Serialize;
 use std::any::type_name;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter};
 use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
 
 pub mod cursor;
+#[cfg(test)]
+mod dev;
+pub mod field_map;
 pub mod firmware;
 pub mod nvidia;
 pub mod pci_efi;
 pub mod pci_legacy;
+pub mod report;
+pub mod validate;
 
 const FIRMWARE_REGION_ALIGN: u64 = 512;
 const FIRMWARE_REGION_STRUCTURE_ALIGN: u64 = 1;
+const FIRMWARE_REGION_STRUCTURE_PROBE_LEN: usize = FIRMWARE_REGION_STRUCTURE_ALIGN as usize * 16;
+const FIRMWARE_REGION_STRUCTURE_SCAN_WINDOW: usize = 64 * 1024;
+
+const DEFAULT_MAX_REGIONS: usize = 1024;
+const PCIR_OFFSET_POSITION: u64 = 0x18;
+const DEFAULT_MAX_IMAGE_BYTES: u64 = 16 * 1024 * 1024;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -28,17 +41,162 @@ pub enum Error {
     InvalidFormat(String),
     #[error("Binary format parsing Error: `{0}`")]
     BinReadError(#[from] binread::Error),
+    #[error("Failed to parse {kind} at offset {offset}: `{source}`")]
+    RegionParse {
+        offset: u64,
+        kind: &'static str,
+        source: binread::Error,
+    },
     #[error("Error: `{0}`")]
     ErrorMessage(String),
+    #[cfg(feature = "postcard")]
+    #[error("Serialization Error: `{0}`")]
+    PostcardError(#[from] postcard::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub max_regions: usize,
+    pub max_image_bytes: u64,
+    /// Stride of the region scan, non-standard dumps may pad images to 256 bytes or not at all
+    pub scan_alignment: u64,
+    /// Keeps a copy of every parsed region in the bundle, duplicating the image data
+    pub retain_regions: bool,
+    /// Emits [`Region::Unknown`] with the raw bytes of regions whose signature matched,
+    /// but which could not be parsed by any candidate
+    pub capture_unknown_regions: bool,
+    /// Fails the parse on structures, tokens and tables that are referenced but malformed,
+    /// instead of skipping them with a warning
+    pub strict: bool,
+    /// Keeps the raw bytes of the PCI images, they are dropped after parsing otherwise
+    pub capture_payloads: bool,
+    /// Records the parse warnings in the bundle, see `FirmwareBundleInfo::warnings`
+    pub collect_warnings: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_regions: DEFAULT_MAX_REGIONS,
+            max_image_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            scan_alignment: FIRMWARE_REGION_ALIGN,
+            retain_regions: false,
+            capture_unknown_regions: false,
+            strict: false,
+            capture_payloads: true,
+            collect_warnings: false,
+        }
+    }
 }
 
 fn stream_position<R: Read + Seek>(reader: &mut R, _ro: &ReadOptions, _: ()) -> BinResult<u64> {
     Ok(reader.stream_position()?)
 }
 
+/// Reads a field and, with the `parse-trace` feature, logs its name, offset and raw bytes.
+fn traced<R: Read + Seek, T: BinRead>(
+    reader: &mut R,
+    ro: &ReadOptions,
+    (name, args): (&'static str, T::Args),
+) -> BinResult<T> {
+    let offset = reader.stream_position()?;
+    let mut value = T::read_options(reader, ro, args)?;
+    value.after_parse(reader, ro, args)?;
+    if cfg!(feature = "parse-trace") {
+        let end = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![0u8; end.saturating_sub(offset) as usize];
+        reader.read_exact(&mut bytes)?;
+        trace!("field {name} @ {offset:#X}: {bytes:02X?}");
+    }
+    Ok(value)
+}
+
+fn read_image_data<R: Read + Seek>(
+    reader: &mut R,
+    _ro: &ReadOptions,
+    (offset_in_firmware, image_length, max_image_bytes): (u64, u16, u64),
+) -> BinResult<Vec<u8>> {
+    let image_bytes = image_length as u64 * 512;
+    if image_bytes > max_image_bytes {
+        return Err(binread::Error::Custom {
+            pos: reader.stream_position()?,
+            err: Box::new(Error::InvalidFormat(format!(
+                "Image size {} exceeds the limit of {} bytes",
+                image_bytes, max_image_bytes
+            ))),
+        });
+    }
+    // A lying image length must not allocate or read past the end of the source, and the scan
+    // continues from the headers so regions inside such an image are still found. The data is
+    // bounded by the end of the source only, so it may include those regions.
+    let position = reader.stream_position()?;
+    reader.seek(SeekFrom::Start(offset_in_firmware))?;
+    let mut data = Vec::new();
+    reader.by_ref().take(image_bytes).read_to_end(&mut data)?;
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(data)
+}
+
+/// Reads the undecoded tail of a `size` bytes structure after its first `decoded` bytes.
+fn read_remaining<R: Read + Seek>(
+    reader: &mut R,
+    _ro: &ReadOptions,
+    (name, size, decoded): (&'static str, u8, u8),
+) -> BinResult<Vec<u8>> {
+    let Some(remaining) = size.checked_sub(decoded) else {
+        return Err(binread::Error::Custom {
+            pos: reader.stream_position()?,
+            err: Box::new(Error::InvalidFormat(format!(
+                "{} size {} is smaller than its {} decoded bytes",
+                name, size, decoded
+            ))),
+        });
+    };
+    let mut data = vec![0u8; remaining as usize];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn check_signature<R: Read + Seek>(
+    reader: &mut R,
+    _ro: &ReadOptions,
+    (offset_in_firmware, pcir_offset, signature): (u64, u16, &'static [u8]),
+) -> BinResult<()> {
+    let pos = offset_in_firmware + pcir_offset as u64;
+    let mut buf = [0u8; 4];
+    reader.seek(SeekFrom::Start(pos))?;
+    reader.read_exact(&mut buf)?;
+    if buf != signature {
+        return Err(binread::Error::Custom {
+            pos,
+            err: Box::new(Error::InvalidFormat(format!(
+                "{} signature not found at pcir_offset {:#X}",
+                String::from_utf8_lossy(signature),
+                pcir_offset
+            ))),
+        });
+    }
+    Ok(())
+}
+
+/// Fills the buffer until the end of the source or the first read error, returns the filled length.
+fn read_available(source: &mut impl Read, buf: &mut [u8]) -> usize {
+    let mut len = 0;
+    while len < buf.len() {
+        match source.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(count) => len += count,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    len
+}
+
 fn align(source: &mut impl Seek, alignment: u64) -> Result<()> {
     let offset = source.stream_position()?;
-    let aligned_offset = offset + (alignment - 1) & !(alignment - 1);
+    let aligned_offset = offset.div_ceil(alignment) * alignment;
     trace!(
         "Align: unaligned position {} aligned position {}",
         offset,
@@ -48,9 +206,31 @@ fn align(source: &mut impl Seek, alignment: u64) -> Result<()> {
     Ok(())
 }
 
-fn read_region<B: binread::BinRead + Debug>(
+/// Parses a table of type `T` at `offset` of `source`, with the error of the failed parse.
+///
+/// The offset is relative to `source`, pass a reader over the legacy image (e.g. a
+/// `ContinuousRegionReader`) for the tables that are referenced by image pointers.
+/// Tables that import a pointers token, such as `MemoryTweakTable` with `PerfPtrsToken`, seek to
+/// the pointer of the token instead, so set that pointer to `offset` in the passed token.
+pub fn parse_table_at<T: binread::BinRead + Debug>(
+    source: &mut (impl Seek + Read),
+    offset: u64,
+    args: T::Args,
+) -> Result<T> {
+    read_region_args(source, offset, args)
+}
+
+fn read_region<B: binread::BinRead<Args = ()> + Debug>(
+    source: &mut (impl Seek + Read),
+    offset_in_firmware: u64,
+) -> Result<B> {
+    read_region_args(source, offset_in_firmware, ())
+}
+
+fn read_region_args<B: binread::BinRead + Debug>(
     source: &mut (impl Seek + Read),
     offset_in_firmware: u64,
+    args: B::Args,
 ) -> Result<B> {
     source.seek(SeekFrom::Start(offset_in_firmware))?;
     trace!(
@@ -58,16 +238,23 @@ fn read_region<B: binread::BinRead + Debug>(
         type_name::<B>(),
         offset_in_firmware
     );
-    let region = source.read_le::<B>();
-    if region.is_err() {
+    let region = source.read_le_args::<B>(args);
+    if let Err(err) = &region {
         trace!(
             "Failed to parse region at {}: {:?}",
             offset_in_firmware,
             region
         );
         source.seek(SeekFrom::Start(offset_in_firmware))?;
+        if let Some(Error::InvalidFormat(message)) = err.custom_err::<Error>() {
+            return Err(Error::InvalidFormat(message.clone()));
+        }
     }
-    Ok(region?)
+    region.map_err(|source| Error::RegionParse {
+        offset: offset_in_firmware,
+        kind: type_name::<B>(),
+        source,
+    })
 }
 
 pub trait FirmwareRegion: Debug {
@@ -77,102 +264,100 @@ pub trait FirmwareRegion: Debug {
         self.offset_in_firmware() + self.region_size()
     }
 
+    /// Absolute `[start, end)` byte range of the region in the firmware file
+    fn firmware_range(&self) -> Range<u64> {
+        self.offset_in_firmware()..self.end_offset_in_firmware()
+    }
+
+    /// Whether the file offset is inside the region, the end offset is exclusive
+    fn contains(&self, firmware_offset: u64) -> bool {
+        self.firmware_range().contains(&firmware_offset)
+    }
+
     fn region_size(&self) -> u64;
+
+    /// `region_size` bounded by the end of a `file_len` bytes firmware file only, the size of an
+    /// image with an inflated length still covers the regions that follow it
+    fn region_size_within_file(&self, file_len: u64) -> u64 {
+        self.region_size()
+            .min(file_len.saturating_sub(self.offset_in_firmware()))
+    }
+
+    /// Short label of the region type, e.g. `nvidia-pci` or `nbsi`
+    fn kind(&self) -> &'static str {
+        "region"
+    }
 }
 
 pub struct RegionIterator<'a, S: Read + Seek> {
     source: &'a mut S,
+    options: ParseOptions,
+    parsers: Vec<RegionParser>,
+    region_count: usize,
 }
 
 impl<'a, S: Read + Seek> RegionIterator<'a, S> {
     pub fn new(source: &'a mut S) -> Self {
-        Self { source }
+        Self::with_options(source, ParseOptions::default())
+    }
+
+    pub fn with_options(source: &'a mut S, options: ParseOptions) -> Self {
+        Self {
+            source,
+            options,
+            parsers: default_region_parsers(),
+            region_count: 0,
+        }
+    }
+
+    /// Registers an additional parser, it's tested after the parsers registered before it
+    pub fn with_parser(mut self, parser: RegionParser) -> Self {
+        self.parsers.push(parser);
+        self
     }
 
     pub fn try_next(&mut self) -> Result<Option<Region>> {
+        let region = self.try_next_region()?;
+        if region.is_some() {
+            if self.region_count >= self.options.max_regions {
+                return Err(Error::InvalidFormat(format!(
+                    "Firmware contains more than {} regions",
+                    self.options.max_regions
+                )));
+            }
+            self.region_count += 1;
+        }
+        Ok(region)
+    }
+
+    fn try_next_region(&mut self) -> Result<Option<Region>> {
         let mut buf = [0u8; FIRMWARE_REGION_ALIGN as usize];
+        let alignment = self.options.scan_alignment.max(1);
 
-        align(&mut self.source, FIRMWARE_REGION_ALIGN)?;
-        while let Ok(_) = self.source.read_exact(&mut buf) {
-            self.source
-                .seek(SeekFrom::Current(-(FIRMWARE_REGION_ALIGN as i64)))?;
+        align(&mut self.source, alignment)?;
+        loop {
             let offset_in_firmware = self.source.stream_position()?;
-            let signature_2 = &buf[0..2];
-            let signature_4 = &buf[0..4];
-
-            trace!(
-                "Testing region at {} for 2-bytes signature: {:02X?}",
-                offset_in_firmware,
-                signature_2
-            );
-            match signature_2 {
-                pci_legacy::PCI_EXPANSION_ROM_HEADER_IDENTIFIER => {
-                    if let Ok(region) = read_region::<pci_efi::EfiPciExpansionRom>(
-                        &mut self.source,
-                        offset_in_firmware,
-                    ) {
-                        return Ok(Some(Region::EfiPciExpansionRom(region)));
-                    }
-                    if let Ok(region) = read_region::<pci_legacy::PciExpansionRom>(
-                        &mut self.source,
-                        offset_in_firmware,
-                    ) {
-                        return Ok(Some(Region::LegacyPciExpansionRom(region)));
-                    }
-                }
-                nvidia::NV_ROM_SIGNATURE => {
-                    if let Ok(region) = read_region::<nvidia::nbsi::NbsiPciExpansionRom>(
-                        &mut self.source,
-                        offset_in_firmware,
-                    ) {
-                        return Ok(Some(Region::NbsiPciExpansionRom(region)));
-                    }
-                    if let Ok(region) = read_region::<nvidia::NvidiaPciExpansionRom>(
-                        &mut self.source,
-                        offset_in_firmware,
-                    ) {
-                        return Ok(Some(Region::NvidiaPciExpansionRom(region)));
-                    }
-                }
-                _ => {
-                    trace!(
-                        "No matches found at {} for 2-bytes signature: {:02X?}",
-                        offset_in_firmware,
-                        signature_2
-                    );
-                }
+            let len = read_available(self.source, &mut buf);
+            if len < 4 {
+                break;
             }
-
-            trace!(
-                "Testing region at {} for 4-bytes signature: {:02X?}",
+            if let Some(region) = Region::read_at_with(
+                self.source,
                 offset_in_firmware,
-                signature_4
-            );
-            match signature_4 {
-                nvidia::NVGI_SIGNATURE => {
-                    if let Ok(region) =
-                        read_region::<nvidia::NvgiRegion>(&mut self.source, offset_in_firmware)
-                    {
-                        return Ok(Some(Region::NvgiRegion(region)));
-                    }
-                }
-                nvidia::RFRD_SIGNATURE => {
-                    if let Ok(region) =
-                        read_region::<nvidia::RfrdRegion>(&mut self.source, offset_in_firmware)
-                    {
-                        return Ok(Some(Region::RfrdRegion(region)));
-                    }
-                }
-                _ => {
-                    trace!(
-                        "No matches found at {} for 4-bytes signature: {:02X?}",
-                        offset_in_firmware,
-                        signature_4
-                    );
-                }
+                &self.options,
+                &self.parsers,
+            )? {
+                return Ok(Some(region));
             }
             self.source
-                .seek(SeekFrom::Start(offset_in_firmware + FIRMWARE_REGION_ALIGN))?;
+                .seek(SeekFrom::Start(offset_in_firmware + alignment))?;
+            if self.options.capture_unknown_regions && has_region_signature(&self.parsers, &buf) {
+                return Ok(Some(Region::Unknown(UnknownRegion {
+                    offset_in_firmware,
+                    signature: [buf[0], buf[1], buf[2], buf[3]],
+                    raw: buf[..len].to_vec(),
+                })));
+            }
         }
 
         Ok(None)
@@ -190,22 +375,280 @@ impl<'a, S: Read + Seek> Iterator for RegionIterator<'a, S> {
 #[derive(Debug, Clone, Serialize)]
 pub enum Region {
     LegacyPciExpansionRom(pci_legacy::PciExpansionRom),
+    /// Open Firmware or PA-RISC image, it's not scanned for NVIDIA structures
+    OpenFirmwareImage(pci_legacy::PciExpansionRom),
+    /// NVIDIA image with HDCP keys, it's bounded but not decoded
+    HdcpImage(nvidia::NvidiaPciExpansionRom),
     EfiPciExpansionRom(pci_efi::EfiPciExpansionRom),
     NvidiaPciExpansionRom(nvidia::NvidiaPciExpansionRom),
     NbsiPciExpansionRom(nvidia::nbsi::NbsiPciExpansionRom),
     NvgiRegion(nvidia::NvgiRegion),
     RfrdRegion(nvidia::RfrdRegion),
+    /// Region with a known signature that failed to parse, see [`ParseOptions::capture_unknown_regions`]
+    Unknown(UnknownRegion),
+    /// Region produced by a parser registered with [`RegionIterator::with_parser`]
+    Custom(CustomRegion),
+}
+
+/// Raw bytes of a region that could not be parsed, limited to the first 512 bytes
+#[derive(Debug, Clone, Serialize)]
+pub struct UnknownRegion {
+    pub offset_in_firmware: u64,
+    pub signature: [u8; 4],
+    pub raw: Vec<u8>,
+}
+
+impl FirmwareRegion for UnknownRegion {
+    fn offset_in_firmware(&self) -> u64 {
+        self.offset_in_firmware
+    }
+
+    fn region_size(&self) -> u64 {
+        self.raw.len() as u64
+    }
+
+    fn kind(&self) -> &'static str {
+        "unknown"
+    }
+}
+
+/// Source of a [`RegionParserFn`], any `Read + Seek` type can be used as one
+pub trait RegionSource: Read + Seek {}
+
+impl<S: Read + Seek> RegionSource for S {}
+
+/// Parses a region at the given offset, returns `Ok(None)` if the bytes are not a region of this type
+pub type RegionParserFn = fn(
+    source: &mut dyn RegionSource,
+    offset_in_firmware: u64,
+    options: &ParseOptions,
+) -> Result<Option<Region>>;
+
+/// Region parser dispatched by the signature at the start of the region
+#[derive(Clone, Copy)]
+pub struct RegionParser {
+    pub signature: &'static [u8],
+    pub parse: RegionParserFn,
+}
+
+impl Debug for RegionParser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegionParser")
+            .field("signature", &self.signature)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RegionParser {
+    pub const fn new(signature: &'static [u8], parse: RegionParserFn) -> Self {
+        Self { signature, parse }
+    }
+
+    fn matches(&self, buf: &[u8]) -> bool {
+        !self.signature.is_empty() && buf.starts_with(self.signature)
+    }
+}
+
+/// Built-in parsers in the order they are tested
+pub fn default_region_parsers() -> Vec<RegionParser> {
+    vec![
+        RegionParser::new(
+            pci_legacy::PCI_EXPANSION_ROM_HEADER_IDENTIFIER,
+            parse_pci_region,
+        ),
+        RegionParser::new(nvidia::NV_ROM_SIGNATURE, parse_nv_region),
+        RegionParser::new(nvidia::NVGI_SIGNATURE, parse_nvgi_region),
+        RegionParser::new(nvidia::RFRD_SIGNATURE, parse_rfrd_region),
+    ]
+}
+
+/// Region of a type unknown to this crate, the bytes can be read back with [`FirmwareRegion::firmware_range`]
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomRegion {
+    pub kind: &'static str,
+    pub offset_in_firmware: u64,
+    pub size: u64,
+}
+
+impl FirmwareRegion for CustomRegion {
+    fn offset_in_firmware(&self) -> u64 {
+        self.offset_in_firmware
+    }
+
+    fn region_size(&self) -> u64 {
+        self.size
+    }
+
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+}
+
+/// Whether the data structure signature is at the `pcir_offset` of the image header, a region
+/// signature without it is a stray match inside other data rather than an image.
+fn has_data_signature(
+    source: &mut (impl Read + Seek),
+    offset_in_firmware: u64,
+    signature: &[u8],
+) -> Result<bool> {
+    let mut pcir_offset = [0u8; 2];
+    source.seek(SeekFrom::Start(offset_in_firmware + PCIR_OFFSET_POSITION))?;
+    if read_available(source, &mut pcir_offset) < pcir_offset.len() {
+        return Ok(false);
+    }
+    let mut buf = [0u8; 4];
+    source.seek(SeekFrom::Start(
+        offset_in_firmware + u16::from_le_bytes(pcir_offset) as u64,
+    ))?;
+    Ok(read_available(source, &mut buf) == buf.len() && buf == signature)
+}
+
+fn has_region_signature(parsers: &[RegionParser], buf: &[u8]) -> bool {
+    parsers.iter().any(|parser| parser.matches(buf))
+}
+
+fn parse_pci_region(
+    mut source: &mut dyn RegionSource,
+    offset_in_firmware: u64,
+    options: &ParseOptions,
+) -> Result<Option<Region>> {
+    if !has_data_signature(
+        &mut source,
+        offset_in_firmware,
+        pci_legacy::PCI_EXPANSION_ROM_DATA_IDENTIFIER,
+    )? {
+        return Ok(None);
+    }
+    match read_region_args::<pci_efi::EfiPciExpansionRom>(
+        &mut source,
+        offset_in_firmware,
+        (options.max_image_bytes,),
+    ) {
+        Ok(region) => return Ok(Some(Region::EfiPciExpansionRom(region))),
+        Err(err @ Error::InvalidFormat(_)) => return Err(err),
+        Err(_) => {}
+    }
+    match read_region_args::<pci_legacy::PciExpansionRom>(
+        &mut source,
+        offset_in_firmware,
+        (options.max_image_bytes,),
+    ) {
+        Ok(region) if region.is_open_firmware() => Ok(Some(Region::OpenFirmwareImage(region))),
+        Ok(region) => Ok(Some(Region::LegacyPciExpansionRom(region))),
+        Err(err @ Error::InvalidFormat(_)) => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+fn parse_nv_region(
+    mut source: &mut dyn RegionSource,
+    offset_in_firmware: u64,
+    options: &ParseOptions,
+) -> Result<Option<Region>> {
+    if let Ok(region) =
+        read_region::<nvidia::nbsi::NbsiPciExpansionRom>(&mut source, offset_in_firmware)
+    {
+        return Ok(Some(Region::NbsiPciExpansionRom(region)));
+    }
+    if !has_data_signature(
+        &mut source,
+        offset_in_firmware,
+        nvidia::NV_PCI_DATA_STRUCTURE_SIGNATURE,
+    )? {
+        return Ok(None);
+    }
+    match read_region_args::<nvidia::NvidiaPciExpansionRom>(
+        &mut source,
+        offset_in_firmware,
+        (options.max_image_bytes,),
+    ) {
+        Ok(region) if region.is_hdcp() => Ok(Some(Region::HdcpImage(region))),
+        Ok(region) => Ok(Some(Region::NvidiaPciExpansionRom(region))),
+        Err(err @ Error::InvalidFormat(_)) => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+fn parse_nvgi_region(
+    mut source: &mut dyn RegionSource,
+    offset_in_firmware: u64,
+    _options: &ParseOptions,
+) -> Result<Option<Region>> {
+    Ok(
+        read_region::<nvidia::NvgiRegion>(&mut source, offset_in_firmware)
+            .ok()
+            .map(Region::NvgiRegion),
+    )
+}
+
+fn parse_rfrd_region(
+    mut source: &mut dyn RegionSource,
+    offset_in_firmware: u64,
+    _options: &ParseOptions,
+) -> Result<Option<Region>> {
+    Ok(
+        read_region::<nvidia::RfrdRegion>(&mut source, offset_in_firmware)
+            .ok()
+            .map(Region::RfrdRegion),
+    )
 }
 
 impl Region {
+    /// Parses a single region at the given offset by its signature, without scanning.
+    pub fn read_at<S: Read + Seek>(
+        source: &mut S,
+        offset_in_firmware: u64,
+        options: &ParseOptions,
+    ) -> Result<Option<Region>> {
+        Self::read_at_with(
+            source,
+            offset_in_firmware,
+            options,
+            &default_region_parsers(),
+        )
+    }
+
+    /// Same as [`Region::read_at`], but tests only the given parsers
+    pub fn read_at_with<S: Read + Seek>(
+        source: &mut S,
+        offset_in_firmware: u64,
+        options: &ParseOptions,
+        parsers: &[RegionParser],
+    ) -> Result<Option<Region>> {
+        let mut buf = [0u8; 4];
+        source.seek(SeekFrom::Start(offset_in_firmware))?;
+        source.read_exact(&mut buf)?;
+
+        trace!(
+            "Testing region at {} for signature: {:02X?}",
+            offset_in_firmware,
+            buf
+        );
+        for parser in parsers.iter().filter(|parser| parser.matches(&buf)) {
+            if let Some(region) = (parser.parse)(source, offset_in_firmware, options)? {
+                return Ok(Some(region));
+            }
+        }
+        trace!(
+            "No matches found at {} for signature: {:02X?}",
+            offset_in_firmware,
+            buf
+        );
+        Ok(None)
+    }
+
     fn firmware_region(&self) -> &dyn FirmwareRegion {
         match self {
             Region::LegacyPciExpansionRom(region) => region,
+            Region::OpenFirmwareImage(region) => region,
+            Region::HdcpImage(region) => region,
             Region::EfiPciExpansionRom(region) => region,
             Region::NvidiaPciExpansionRom(region) => region,
             Region::NbsiPciExpansionRom(region) => region,
             Region::NvgiRegion(region) => region,
             Region::RfrdRegion(region) => region,
+            Region::Unknown(region) => region,
+            Region::Custom(region) => region,
         }
     }
 }
@@ -218,54 +661,136 @@ impl FirmwareRegion for Region {
     fn region_size(&self) -> u64 {
         self.firmware_region().region_size()
     }
+
+    fn kind(&self) -> &'static str {
+        self.firmware_region().kind()
+    }
+}
+
+/// Regions are ordered by their offset in the firmware file
+impl Ord for Region {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.offset_in_firmware().cmp(&other.offset_in_firmware())
+    }
+}
+
+impl PartialOrd for Region {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Region {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
+impl Eq for Region {}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum RegionStructure {
     BiosInformationTable(bit::BITStructure),
     DeviceControlBlock(dcb::DeviceControlBlock),
 }
 
+impl RegionStructure {
+    /// Offset of the structure in the reader it was found with
+    pub fn offset_in_region(&self) -> u64 {
+        match self {
+            RegionStructure::BiosInformationTable(bit) => bit.offset_in_region,
+            RegionStructure::DeviceControlBlock(dcb) => dcb.offset_in_region,
+        }
+    }
+
+    /// Firmware offset of the region that contains the structure, recorded by the firmware parse
+    pub fn region_offset_in_firmware(&self) -> Option<u64> {
+        match self {
+            RegionStructure::BiosInformationTable(bit) => bit.region_offset_in_firmware,
+            RegionStructure::DeviceControlBlock(dcb) => dcb.region_offset_in_firmware,
+        }
+    }
+
+    fn set_region_offset_in_firmware(&mut self, offset: u64) {
+        match self {
+            RegionStructure::BiosInformationTable(bit) => {
+                bit.region_offset_in_firmware = Some(offset)
+            }
+            RegionStructure::DeviceControlBlock(dcb) => {
+                dcb.region_offset_in_firmware = Some(offset)
+            }
+        }
+    }
+}
+
 pub struct RegionStructureIterator<'a, S: Read + Seek> {
     source: &'a mut S,
+    options: ParseOptions,
+    // Scans a buffered window instead of reading the probe at every position
+    window: Vec<u8>,
 }
 
 impl<'a, S: Read + Seek> RegionStructureIterator<'a, S> {
     pub fn new(source: &'a mut S) -> Self {
-        Self { source }
+        Self::with_options(source, ParseOptions::default())
     }
 
-    pub fn try_next(&mut self) -> Result<Option<RegionStructure>> {
-        let mut buf = [0u8; FIRMWARE_REGION_STRUCTURE_ALIGN as usize * 16];
+    pub fn with_options(source: &'a mut S, options: ParseOptions) -> Self {
+        Self {
+            source,
+            options,
+            window: vec![
+                0u8;
+                FIRMWARE_REGION_STRUCTURE_SCAN_WINDOW + FIRMWARE_REGION_STRUCTURE_PROBE_LEN
+            ],
+        }
+    }
 
+    pub fn try_next(&mut self) -> Result<Option<RegionStructure>> {
         trace!("Iterating over structures in region.");
         align(&mut self.source, FIRMWARE_REGION_STRUCTURE_ALIGN)?;
-        while let Ok(_) = self.source.read_exact(&mut buf) {
-            self.source.seek(SeekFrom::Current(-(buf.len() as i64)))?;
-            let offset_in_firmware = self.source.stream_position()?;
-            trace!(
-                "Testing region at {} for region structures: {:02X?}",
-                offset_in_firmware,
-                buf
-            );
-            if &buf[2..6] == bit::BIT_SIGNATURE {
-                if let Ok(bit_structure) =
-                    read_region::<bit::BITStructure>(&mut self.source, offset_in_firmware)
-                {
-                    return Ok(Some(RegionStructure::BiosInformationTable(bit_structure)));
-                }
+        let mut window_offset = self.source.stream_position()?;
+        loop {
+            self.source.seek(SeekFrom::Start(window_offset))?;
+            let window_len = read_available(&mut self.source, &mut self.window);
+            if window_len < FIRMWARE_REGION_STRUCTURE_PROBE_LEN {
+                break;
             }
-            if &buf[6..10] == dcb::DCB_SIGNATURE {
-                if let Ok(dcb_structure) =
-                    read_region::<dcb::DeviceControlBlock>(&mut self.source, offset_in_firmware)
-                {
-                    return Ok(Some(RegionStructure::DeviceControlBlock(dcb_structure)));
+            let mut probe_start = 0;
+            while probe_start + FIRMWARE_REGION_STRUCTURE_PROBE_LEN <= window_len {
+                let buf =
+                    &self.window[probe_start..probe_start + FIRMWARE_REGION_STRUCTURE_PROBE_LEN];
+                let offset_in_firmware = window_offset + probe_start as u64;
+                trace!(
+                    "Testing region at {} for region structures: {:02X?}",
+                    offset_in_firmware,
+                    buf
+                );
+                if &buf[2..6] == bit::BIT_SIGNATURE {
+                    match read_region::<bit::BITStructure>(&mut self.source, offset_in_firmware) {
+                        Ok(bit_structure) => {
+                            return Ok(Some(RegionStructure::BiosInformationTable(bit_structure)))
+                        }
+                        Err(err) if self.options.strict => return Err(err),
+                        Err(_) => {}
+                    }
+                }
+                if &buf[6..10] == dcb::DCB_SIGNATURE {
+                    match read_region::<dcb::DeviceControlBlock>(
+                        &mut self.source,
+                        offset_in_firmware,
+                    ) {
+                        Ok(dcb_structure) => {
+                            return Ok(Some(RegionStructure::DeviceControlBlock(dcb_structure)))
+                        }
+                        Err(err) if self.options.strict => return Err(err),
+                        Err(_) => {}
+                    }
                 }
-            }
 
-            self.source.seek(SeekFrom::Start(
-                offset_in_firmware + FIRMWARE_REGION_STRUCTURE_ALIGN,
-            ))?;
+                probe_start += FIRMWARE_REGION_STRUCTURE_ALIGN as usize;
+            }
+            window_offset += probe_start as u64;
         }
 
         Ok(None)
@@ -280,7 +805,43 @@ impl<'a, S: Read + Seek> Iterator for RegionStructureIterator<'a, S> {
     }
 }
 
-#[derive(BinRead, Clone, Copy, Serialize)]
+/// Reads only the vBIOS version from the BIOS token of the first legacy image, without parsing
+/// the other tokens and structures.
+pub fn quick_version<S: Read + Seek>(source: &mut S) -> Result<Option<String>> {
+    let preamble_len =
+        firmware::FirmwareBundleInfo::detect_preamble_len(source, &ParseOptions::default())?;
+    let source = &mut cursor::OffsetReader::new(source, preamble_len);
+    let mut region_iterator = RegionIterator::new(source);
+    let legacy = loop {
+        match region_iterator.try_next()? {
+            Some(Region::LegacyPciExpansionRom(legacy)) => break legacy,
+            Some(_) => {}
+            None => return Ok(None),
+        }
+    };
+    let regions: Vec<&dyn FirmwareRegion> = vec![&legacy];
+    let mut reader = cursor::ContinuousRegionReader::new(source, regions);
+    let Some(bit) =
+        RegionStructureIterator::new(&mut reader).find_map(|structure| match structure {
+            RegionStructure::BiosInformationTable(bit) => Some(bit),
+            RegionStructure::DeviceControlBlock(_) => None,
+        })
+    else {
+        return Ok(None);
+    };
+    for token in bit
+        .tokens
+        .iter()
+        .filter(|token| token.id == bit::BIOS_TOKEN_ID)
+    {
+        if let bit::BITTokenType::Bios(bios_token) = token.data(&mut reader)? {
+            return Ok(Some(bios_token.version()));
+        }
+    }
+    Ok(None)
+}
+
+#[derive(BinRead, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct VersionHex4([u8; 4]);
 
 impl Debug for VersionHex4 {
@@ -307,56 +868,950 @@ impl VersionHex4 {
             Some(self)
         }
     }
+
+    /// Bytes in the firmware order
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    pub fn from_u32_le(value: u32) -> Self {
+        Self(value.to_le_bytes())
+    }
+
+    pub fn to_u32_le(&self) -> u32 {
+        u32::from_le_bytes(self.0)
+    }
+
+    /// Formats the bytes in the firmware order, unlike `Debug` and `Display` that print them reversed
+    pub fn forward(&self) -> String {
+        format!(
+            "{:02X}.{:02X}.{:02X}.{:02X}",
+            self.0[0], self.0[1], self.0[2], self.0[3]
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::firmware::FirmwareBundleInfo;
+    use crate::dev::get_rom_file;
+    use crate::firmware::{FirmwareBundleInfo, FirmwareInfo, TableStatus};
+    use crate::nvidia::bit::perf::RawTable;
+    use crate::nvidia::bit::{BITTokenType, BiosDataToken, BIT_SIGNATURE};
+    use crate::nvidia::dcb::{ConnectorOutputKind, GpioAssignmentTable};
+    use crate::nvidia::{NvidiaPciDataExtendedFlags, NvidiaPciExpansionRom, NVGI_SIGNATURE};
+    use crate::pci_efi::EfiPciExpansionRomMachineType;
+    use crate::pci_legacy::{
+        PciExpansionRom, PciExpansionRomCodeType, PCI_EXPANSION_ROM_HEADER_IDENTIFIER,
+    };
+    use crate::validate::{CHECKSUM_CHECK, LAYOUT_CHECK};
+    use crate::{
+        parse_table_at, quick_version, read_region_args, CustomRegion, Error, FirmwareRegion,
+        ParseOptions, Region, RegionIterator, RegionParser, RegionSource, RegionStructureIterator,
+        VersionHex4, DEFAULT_MAX_IMAGE_BYTES,
+    };
+    use binread::BinReaderExt;
     use log::LevelFilter;
-    use reqwest::Url;
     use simplelog::{Config, TestLogger};
-    use std::fs::File;
-    use std::{env, fs};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    // Tests share the process-wide logger, only the first one installs it
+    fn init_logger() {
+        let _ = TestLogger::init(LevelFilter::Debug, Config::default());
+    }
 
-    const CACHE_FOLDER: &str = "nv-rom-parser-cache";
+    fn image_with_wrong_pcir_offset(signature: &[u8], data_signature: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 512];
+        image[0..2].copy_from_slice(signature);
+        image[0x18..0x1A].copy_from_slice(&0x40u16.to_le_bytes());
+        image[0x50..0x54].copy_from_slice(data_signature);
+        image
+    }
 
     #[test]
-    fn test_3060ti() {
-        TestLogger::init(LevelFilter::Debug, Config::default()).unwrap();
-        let mut rom_file = get_rom_file(
-            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
-        );
-        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
-        println!("Firmware: {:#?}", &firmware_bundle);
-        println!("\n\n\n{:#?}", firmware_bundle.v_bios_info())
+    fn test_wrong_pcir_offset() {
+        let mut cursor = Cursor::new(image_with_wrong_pcir_offset(
+            PCI_EXPANSION_ROM_HEADER_IDENTIFIER,
+            b"PCIR",
+        ));
+        let result =
+            read_region_args::<PciExpansionRom>(&mut cursor, 0, (DEFAULT_MAX_IMAGE_BYTES,));
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFormat(message)) if message == "PCIR signature not found at pcir_offset 0x40"
+        ));
+        assert!(RegionIterator::new(&mut cursor).next().is_none());
     }
 
     #[test]
-    fn test_3060ti_memory_clock() {
-        TestLogger::init(LevelFilter::Debug, Config::default()).unwrap();
-        let mut rom_file = get_rom_file(
-            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
-        );
-        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
-        if let Some(memory_clock_table) = firmware_bundle
-            .firmwares
-            .first()
-            .and_then(|f| f.legacy_pci_image.as_ref())
-            .and_then(|i| i.memory_clock_table.as_ref())
-        {
-            println!("Memory clock table: {:?}", &memory_clock_table);
-            for entry in &memory_clock_table.entries {
-                println!("Entry: {:?}", entry.base_entry.unknown)
-            }
-        }
+    fn test_capture_unknown_regions() {
+        let image = image_with_wrong_pcir_offset(PCI_EXPANSION_ROM_HEADER_IDENTIFIER, b"PCIR");
+        let mut cursor = Cursor::new(image.clone());
+        let options = ParseOptions {
+            capture_unknown_regions: true,
+            ..ParseOptions::default()
+        };
+        let regions: Vec<Region> = RegionIterator::with_options(&mut cursor, options).collect();
+
+        assert_eq!(1, regions.len());
+        let Region::Unknown(unknown) = &regions[0] else {
+            panic!("Expected unknown region, got {:?}", regions[0]);
+        };
+        assert_eq!(0, unknown.offset_in_firmware);
+        assert_eq!(b"\x55\xAA\0\0", &unknown.signature);
+        assert_eq!(image, unknown.raw);
+    }
+
+    fn legacy_image() -> Vec<u8> {
+        let mut image = vec![0u8; 512];
+        image[0..2].copy_from_slice(PCI_EXPANSION_ROM_HEADER_IDENTIFIER);
+        image[0x18..0x1A].copy_from_slice(&0x1Cu16.to_le_bytes());
+        image[0x1C..0x20].copy_from_slice(b"PCIR");
+        image[0x20..0x22].copy_from_slice(&0x10DEu16.to_le_bytes());
+        image[0x2C..0x2E].copy_from_slice(&1u16.to_le_bytes());
+        image[0x31] = 0x80;
+        image
+    }
+
+    fn efi_image(machine_type: EfiPciExpansionRomMachineType) -> Vec<u8> {
+        let mut image = legacy_image();
+        image[4..8].copy_from_slice(b"\xf1\x0e\0\0");
+        image[8..0x0A].copy_from_slice(&0x0Bu16.to_le_bytes());
+        image[0x0A..0x0C].copy_from_slice(&(machine_type as u16).to_le_bytes());
+        image
     }
 
     #[test]
-    fn test_3060ti_memory_tweak() {
-        TestLogger::init(LevelFilter::Debug, Config::default()).unwrap();
-        let mut rom_file = get_rom_file(
-            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+    fn test_multiple_efi_images() {
+        let mut bytes = legacy_image();
+        bytes.extend(efi_image(EfiPciExpansionRomMachineType::X64));
+        bytes.extend(efi_image(EfiPciExpansionRomMachineType::Arm64));
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+
+        let firmware = &firmware_bundle.firmwares[0];
+        assert_eq!(2, firmware.efi_pci_images().count());
+        assert_eq!(
+            EfiPciExpansionRomMachineType::X64,
+            firmware.efi_pci_image.as_ref().unwrap().machine_type()
         );
+        assert_eq!(3, firmware.pci_images().len());
+        assert_eq!(
+            vec![
+                EfiPciExpansionRomMachineType::X64,
+                EfiPciExpansionRomMachineType::Arm64
+            ],
+            firmware_bundle.efi_architectures()
+        );
+    }
+
+    #[test]
+    fn test_preamble() {
+        let mut bytes = vec![0xA5u8; 512];
+        bytes.extend(legacy_image());
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+
+        assert_eq!(512, firmware_bundle.preamble_len);
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        assert_eq!(0, image.image.offset_in_firmware());
+        let results = firmware_bundle.validate(&mut Cursor::new(&bytes)).unwrap();
+        assert!(results
+            .iter()
+            .filter(|result| result.check == LAYOUT_CHECK)
+            .all(|result| result.passed()));
+
+        let mut offsets = Vec::new();
+        FirmwareBundleInfo::parse_streaming(&mut Cursor::new(&bytes), |region| {
+            offsets.push(region.offset_in_firmware())
+        })
+        .unwrap();
+        assert_eq!(vec![0], offsets);
+        assert_eq!(None, quick_version(&mut Cursor::new(&bytes)).unwrap());
+
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&legacy_image()).unwrap();
+        assert_eq!(0, firmware_bundle.preamble_len);
+    }
+
+    #[test]
+    fn test_subsystem_id_from_plat_info() {
+        let mut nbsi = vec![0u8; 512];
+        nbsi[0..2].copy_from_slice(b"VN");
+        nbsi[0x16..0x18].copy_from_slice(&0x60u16.to_le_bytes());
+        nbsi[0x18..0x1A].copy_from_slice(&0x20u16.to_le_bytes());
+        nbsi[0x20..0x24].copy_from_slice(b"NPDS");
+        nbsi[0x30..0x32].copy_from_slice(&1u16.to_le_bytes());
+        nbsi[0x35] = 0x80;
+        let mut directory = b"ISBN".to_vec();
+        directory.extend_from_slice(&0u32.to_le_bytes());
+        directory.extend_from_slice(&[1, 0]);
+        directory.extend_from_slice(b"PI");
+        directory.extend_from_slice(&0u64.to_le_bytes());
+        directory.extend_from_slice(b"PI");
+        directory.extend_from_slice(&24u32.to_le_bytes());
+        directory.extend_from_slice(&[0, 0]);
+        directory.extend_from_slice(&0xA1u32.to_le_bytes());
+        directory.extend_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        nbsi[0x60..0x60 + directory.len()].copy_from_slice(&directory);
+
+        let mut bytes = legacy_image();
+        bytes.extend(nbsi);
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+
+        assert!(firmware_bundle.nbsi_pci_expansion_rom.is_some());
+        assert_eq!(
+            0,
+            firmware_bundle.firmwares[0].data_headers_extended().count()
+        );
+        assert_eq!(
+            Some(VersionHex4([0x12, 0x34, 0x56, 0x78]).to_string()),
+            firmware_bundle.v_bios_info()[0].subsystem_id
+        );
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let image = legacy_image();
+        let firmware_bundle: FirmwareBundleInfo = image.as_slice().try_into().unwrap();
+        assert!(firmware_bundle.firmwares[0].legacy_pci_image.is_some());
+
+        let result: Result<FirmwareBundleInfo, Error> = image.try_into();
+        assert!(result.unwrap().firmwares[0].legacy_pci_image.is_some());
+    }
+
+    #[test]
+    fn test_has_ifr_container() {
+        let mut firmware_bundle = FirmwareBundleInfo::parse_bytes(&legacy_image()).unwrap();
+        assert!(!firmware_bundle.has_ifr_container());
+
+        let mut nvgi = Vec::from(NVGI_SIGNATURE);
+        nvgi.extend_from_slice(&[0; 8]);
+        let nvgi_region = Cursor::new(nvgi).read_le().unwrap();
+        firmware_bundle.firmwares[0].nvgi_regions.push(nvgi_region);
+        assert!(firmware_bundle.has_ifr_container());
+    }
+
+    #[test]
+    fn test_strict_structures() {
+        let mut bytes = vec![0xFF, 0xB8];
+        bytes.extend_from_slice(BIT_SIGNATURE);
+        bytes.extend_from_slice(&[0, 1, 12, 6, 200, 0]);
+        bytes.extend_from_slice(&[0; 10]);
+        let mut cursor = Cursor::new(bytes);
+
+        assert!(RegionStructureIterator::new(&mut cursor)
+            .try_next()
+            .unwrap()
+            .is_none());
+
+        cursor.set_position(0);
+        let options = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        assert!(RegionStructureIterator::with_options(&mut cursor, options)
+            .try_next()
+            .is_err());
+    }
+
+    #[test]
+    fn test_capture_payloads() {
+        let image = legacy_image();
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&image).unwrap();
+        let legacy = firmware_bundle.firmwares[0].legacy_pci_image.as_ref();
+        assert_eq!(512, legacy.unwrap().image.data.len());
+
+        let options = ParseOptions {
+            capture_payloads: false,
+            retain_regions: true,
+            ..ParseOptions::default()
+        };
+        let firmware_bundle =
+            FirmwareBundleInfo::parse_with_options(&mut Cursor::new(&image), &options).unwrap();
+        let legacy = firmware_bundle.firmwares[0].legacy_pci_image.as_ref();
+        assert!(legacy.unwrap().image.data.is_empty());
+        let Region::LegacyPciExpansionRom(region) = &firmware_bundle.regions[0] else {
+            panic!("Unexpected region {:?}", firmware_bundle.regions[0]);
+        };
+        assert!(region.data.is_empty());
+    }
+
+    #[test]
+    fn test_inflated_image_length() {
+        let mut image = legacy_image();
+        image[0x2C..0x2E].copy_from_slice(&0x100u16.to_le_bytes());
+        image[0x31] = 0;
+        let mut bytes = image;
+        bytes.extend(legacy_image());
+        let file_len = bytes.len() as u64;
+        let mut cursor = Cursor::new(bytes);
+
+        let regions: Vec<Region> = RegionIterator::new(&mut cursor).collect();
+        assert_eq!(2, regions.len());
+        let Region::LegacyPciExpansionRom(inflated) = &regions[0] else {
+            panic!("Unexpected region {:?}", regions[0]);
+        };
+        assert_eq!(0x100 * 512, inflated.region_size());
+        // Bounded by the end of the file only, so the second image is included
+        assert_eq!(1024, inflated.region_size_within_file(file_len));
+        assert_eq!(1024, inflated.data.len());
+        assert!(inflated.contains(regions[1].offset_in_firmware()));
+        assert_eq!(512, regions[1].region_size_within_file(file_len));
+    }
+
+    #[test]
+    fn test_region_contains() {
+        let mut bytes = vec![0u8; 512];
+        bytes.extend(legacy_image());
+        let regions: Vec<Region> = RegionIterator::new(&mut Cursor::new(bytes)).collect();
+        let region = &regions[0];
+
+        assert!(!region.contains(511));
+        assert!(region.contains(512));
+        assert!(region.contains(1023));
+        assert!(!region.contains(1024));
+    }
+
+    #[test]
+    fn test_region_ordering() {
+        let mut bytes = legacy_image();
+        bytes[0x31] = 0;
+        bytes.extend(legacy_image());
+        let mut regions: Vec<Region> = RegionIterator::new(&mut Cursor::new(bytes)).collect();
+        regions.reverse();
+        assert!(regions[0] > regions[1]);
+
+        regions.sort();
+        assert_eq!(
+            vec![0, 512],
+            regions
+                .iter()
+                .map(|region| region.offset_in_firmware())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_rfrd_image() {
+        let mut bytes = vec![0u8; 512];
+        bytes[0..4].copy_from_slice(b"RFRD");
+        bytes[8..12].copy_from_slice(&0x200u32.to_le_bytes());
+        bytes.extend(legacy_image());
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+        let firmware = &firmware_bundle.firmwares[0];
+
+        assert_eq!(Some(0), firmware.rfrd_image_index);
+        assert_eq!(0x200, firmware.rfrd_image().unwrap().offset_in_firmware());
+    }
+
+    #[test]
+    fn test_oversized_rfrd_image() {
+        let mut bytes = vec![0u8; 0x210];
+        bytes[0..4].copy_from_slice(b"RFRD");
+        bytes[8..12].copy_from_slice(&0x210u32.to_le_bytes());
+        let mut image = legacy_image();
+        image[0x2C..0x2E].copy_from_slice(&2u16.to_le_bytes());
+        bytes.extend(image);
+        bytes.resize(0x600, 0);
+        let options = ParseOptions {
+            max_image_bytes: 512,
+            collect_warnings: true,
+            ..ParseOptions::default()
+        };
+        let firmware_bundle =
+            FirmwareBundleInfo::parse_with_options(&mut Cursor::new(&bytes), &options).unwrap();
+
+        assert_eq!(None, firmware_bundle.firmwares[0].rfrd_image_index);
+        assert!(firmware_bundle
+            .warnings
+            .iter()
+            .any(|warning| warning.starts_with("PCI image referenced by RFRD at 0x210")));
+    }
+
+    #[test]
+    fn test_unaligned_rfrd_image() {
+        let mut bytes = vec![0u8; 0x210];
+        bytes[0..4].copy_from_slice(b"RFRD");
+        bytes[8..12].copy_from_slice(&0x210u32.to_le_bytes());
+        bytes.extend(legacy_image());
+        bytes.resize(0x600, 0);
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+        let firmware = &firmware_bundle.firmwares[0];
+
+        assert_eq!(Some(0), firmware.rfrd_image_index);
+        assert_eq!(0x210, firmware.rfrd_image().unwrap().offset_in_firmware());
+        assert_eq!(
+            0x210,
+            firmware
+                .legacy_pci_image
+                .as_ref()
+                .unwrap()
+                .image
+                .offset_in_firmware()
+        );
+    }
+
+    #[test]
+    fn test_region_at() {
+        let mut bytes = vec![0u8; 512];
+        bytes[0..4].copy_from_slice(b"RFRD");
+        bytes.extend(legacy_image());
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            0..16,
+            firmware_bundle.region_at(4).unwrap().firmware_range()
+        );
+        assert_eq!(
+            0x200..0x400,
+            firmware_bundle.region_at(0x210).unwrap().firmware_range()
+        );
+        assert!(firmware_bundle.region_at(0x100).is_none());
+        assert!(firmware_bundle.region_at(0x400).is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut image = legacy_image();
+        let sum = image.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        image[511] = sum.wrapping_neg();
+        let mut cursor = Cursor::new(image.clone());
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut cursor).unwrap();
+        let results = firmware_bundle.validate(&mut cursor).unwrap();
+
+        assert_eq!(3, results.len());
+        assert!(results.iter().all(|result| result.passed()));
+
+        let mut cursor = Cursor::new(image[..0x180].to_vec());
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut cursor).unwrap();
+        let failures: Vec<_> = firmware_bundle
+            .validate(&mut cursor)
+            .unwrap()
+            .into_iter()
+            .filter(|result| !result.passed())
+            .collect();
+
+        assert_eq!(2, failures.len());
+        assert_eq!(CHECKSUM_CHECK, failures[0].check);
+        assert_eq!(LAYOUT_CHECK, failures[1].check);
+        assert_eq!(Some(0), failures[1].offset);
+    }
+
+    #[test]
+    fn test_parse_table_at() {
+        let mut bytes = vec![0xFF; 16];
+        bytes.extend_from_slice(&[0x41, 6, 1, 5, 0, 0, 0x05, 7, 0, 0, 0]);
+        let mut cursor = Cursor::new(bytes);
+
+        let table: GpioAssignmentTable = parse_table_at(&mut cursor, 16, ()).unwrap();
+        assert_eq!(1, table.entries.len());
+
+        let err = parse_table_at::<GpioAssignmentTable>(&mut cursor, 0, ()).unwrap_err();
+        assert!(matches!(err, Error::RegionParse { offset: 0, .. }));
+
+        let mut bytes = vec![0xFF; 16];
+        bytes.extend_from_slice(&[0x10, 4, 2, 1, 0x10, 0x20]);
+        let table: RawTable =
+            parse_table_at(&mut Cursor::new(bytes), 16, (16, "DIDT table")).unwrap();
+        assert_eq!(vec![0x10, 0x20], table.entries[0].unknown);
+    }
+
+    #[test]
+    fn test_structure_regions() {
+        let mut nv_image = legacy_image();
+        nv_image[0..2].copy_from_slice(b"VN");
+        nv_image[0x1C..0x20].copy_from_slice(b"NPDS");
+        nv_image[0x30] = PciExpansionRomCodeType::NvidiaX86Extension as u8;
+        nv_image[0x40..0x42].copy_from_slice(&[0xFF, 0xB8]);
+        nv_image[0x42..0x46].copy_from_slice(BIT_SIGNATURE);
+        nv_image[0x46..0x4C].copy_from_slice(&[0, 1, 12, 6, 0, 0]);
+        let mut bytes = legacy_image();
+        bytes[0x31] = 0;
+        bytes.extend(nv_image);
+
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+        let legacy = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        let bit = legacy.bit_table_structure.as_ref().unwrap();
+        assert_eq!(0x240, bit.offset_in_region);
+        assert_eq!(Some(512), bit.region_offset_in_firmware);
+    }
+
+    #[test]
+    fn test_fix_checksums() {
+        let mut image = legacy_image();
+        image[511] = 0x12;
+        let mut efi = efi_image(EfiPciExpansionRomMachineType::X64);
+        efi[511] = 0x34;
+        image.extend(efi);
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&image).unwrap();
+
+        let fixes = firmware_bundle.fix_checksums(&mut image).unwrap();
+        assert_eq!(2, fixes.len());
+        assert_eq!(("legacy", 511), (fixes[0].structure, fixes[0].offset));
+        assert_eq!(("efi", 1023), (fixes[1].structure, fixes[1].offset));
+        let sum = |bytes: &[u8]| bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        assert_eq!(0, sum(&image[..512]));
+        assert_eq!(0, sum(&image[512..]));
+        let results = firmware_bundle
+            .validate(&mut Cursor::new(image.clone()))
+            .unwrap();
+        assert!(results.iter().all(|result| result.passed()));
+
+        assert!(firmware_bundle
+            .fix_checksums(&mut image)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_serialization() {
+        let mut image = legacy_image();
+        image[0x40..0x42].copy_from_slice(&[0xFF, 0xB8]);
+        image[0x42..0x46].copy_from_slice(BIT_SIGNATURE);
+        image[0x46..0x4C].copy_from_slice(&[0, 1, 12, 6, 2, 0]);
+        image[0x4C..0x52].copy_from_slice(&[0x63, 2, 4, 0, 0x80, 0]);
+        image[0x52..0x58].copy_from_slice(&[0x4E, 1, 0, 0, 0x80, 0]);
+        image[0x80..0x84].copy_from_slice(&0x11223344u32.to_le_bytes());
+
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&image).unwrap();
+        let legacy = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        assert!(matches!(
+            legacy.bit_tokens_data[..],
+            [BITTokenType::Nop, BITTokenType::Ptrs32Bit(_)]
+        ));
+        assert_eq!(vec![1, 2], legacy.bit_tokens_versions);
+
+        let json = serde_json::to_string(&firmware_bundle).unwrap();
+        let reparsed = FirmwareBundleInfo::parse_bytes(&image).unwrap();
+        assert_eq!(json, serde_json::to_string(&reparsed).unwrap());
+    }
+
+    #[test]
+    fn test_parse_all_concatenated_images() {
+        let mut bytes = legacy_image();
+        bytes.extend(legacy_image());
+
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+        assert_eq!(1, firmware_bundle.firmwares.len());
+
+        let firmware_bundles =
+            FirmwareBundleInfo::parse_all(&mut Cursor::new(bytes), &ParseOptions::default())
+                .unwrap();
+        let offsets: Vec<u64> = firmware_bundles
+            .iter()
+            .flat_map(|firmware_bundle| &firmware_bundle.firmwares)
+            .filter_map(|firmware| firmware.legacy_pci_image.as_ref())
+            .map(|image| image.image.offset_in_firmware())
+            .collect();
+        assert_eq!(vec![0, 512], offsets);
+    }
+
+    #[test]
+    fn test_parse_all_different_pcir_offsets() {
+        let mut bytes = legacy_image();
+        bytes[0x31] = 0;
+        let mut second = vec![0u8; 512];
+        second[0..2].copy_from_slice(PCI_EXPANSION_ROM_HEADER_IDENTIFIER);
+        second[0x18..0x1A].copy_from_slice(&0x20u16.to_le_bytes());
+        second[0x20..0x24].copy_from_slice(b"PCIR");
+        second[0x24..0x26].copy_from_slice(&0x10DEu16.to_le_bytes());
+        second[0x30..0x32].copy_from_slice(&1u16.to_le_bytes());
+        second[0x35] = 0x80;
+        bytes.extend(second);
+
+        let firmware_bundles =
+            FirmwareBundleInfo::parse_all(&mut Cursor::new(bytes), &ParseOptions::default())
+                .unwrap();
+        let offsets: Vec<Vec<u64>> = firmware_bundles
+            .iter()
+            .map(|firmware_bundle| {
+                firmware_bundle
+                    .firmwares
+                    .iter()
+                    .filter_map(|firmware| firmware.legacy_pci_image.as_ref())
+                    .map(|image| image.image.offset_in_firmware())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(vec![vec![0], vec![512]], offsets);
+    }
+
+    #[test]
+    fn test_open_firmware_image() {
+        let mut image = legacy_image();
+        image[0x30] = PciExpansionRomCodeType::OpenFirmwareStandardForPci as u8;
+        let region = Region::read_at(&mut Cursor::new(&image), 0, &ParseOptions::default());
+
+        assert!(matches!(region, Ok(Some(Region::OpenFirmwareImage(_)))));
+
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&image).unwrap();
+        let firmware = &firmware_bundle.firmwares[0];
+        assert!(firmware.legacy_pci_image.is_none());
+        assert_eq!(1, firmware.open_firmware_images.len());
+    }
+
+    #[test]
+    fn test_region_kind() {
+        let mut image = legacy_image();
+        let region = Region::read_at(&mut Cursor::new(&image), 0, &ParseOptions::default());
+        assert_eq!("legacy", region.unwrap().unwrap().kind());
+
+        image[0x30] = PciExpansionRomCodeType::OpenFirmwareStandardForPci as u8;
+        let region = Region::read_at(&mut Cursor::new(&image), 0, &ParseOptions::default());
+        assert_eq!("open-firmware", region.unwrap().unwrap().kind());
+    }
+
+    fn parse_test_region(
+        _source: &mut dyn RegionSource,
+        offset_in_firmware: u64,
+        _options: &ParseOptions,
+    ) -> crate::Result<Option<Region>> {
+        Ok(Some(Region::Custom(CustomRegion {
+            kind: "test",
+            offset_in_firmware,
+            size: 512,
+        })))
+    }
+
+    #[test]
+    fn test_region_parser() {
+        let mut bytes = b"TEST".to_vec();
+        bytes.resize(512, 0);
+        bytes.extend(legacy_image());
+        let mut cursor = Cursor::new(bytes);
+
+        let regions: Vec<Region> = RegionIterator::new(&mut cursor).collect();
+        assert_eq!(1, regions.len());
+
+        cursor.set_position(0);
+        let regions: Vec<Region> = RegionIterator::new(&mut cursor)
+            .with_parser(RegionParser::new(b"TEST", parse_test_region))
+            .collect();
+        assert_eq!(2, regions.len());
+        assert_eq!("test", regions[0].kind());
+        assert_eq!(0..512, regions[0].firmware_range());
+        assert!(matches!(regions[1], Region::LegacyPciExpansionRom(_)));
+    }
+
+    #[test]
+    fn test_standalone_legacy_image() {
+        let mut nv_image = legacy_image();
+        nv_image[0..2].copy_from_slice(b"VN");
+        nv_image[0x1C..0x20].copy_from_slice(b"NPDS");
+        nv_image[0x30] = PciExpansionRomCodeType::NvidiaX86Extension as u8;
+        nv_image[0x100..0x104].copy_from_slice(b"DATA");
+        let mut bytes = legacy_image();
+        bytes.extend_from_slice(&[0xFF; 512]);
+        bytes.extend_from_slice(&nv_image);
+        let mut cursor = Cursor::new(bytes);
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut cursor).unwrap();
+
+        let standalone = firmware_bundle.firmwares[0]
+            .standalone_legacy_image(&mut cursor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(1024, standalone.len());
+        assert_eq!(b"DATA", &standalone[0x300..0x304]);
+
+        let standalone_bundle = FirmwareBundleInfo::parse_bytes(&standalone).unwrap();
+        let firmware = &standalone_bundle.firmwares[0];
+        assert!(firmware.legacy_pci_image.is_some());
+        assert_eq!(1, firmware.nv_pci_expansion_roms.len());
+        assert_eq!(512, firmware.nv_pci_expansion_roms[0].offset_in_firmware());
+    }
+
+    #[test]
+    fn test_hdcp_image() {
+        let mut image = legacy_image();
+        image[0..2].copy_from_slice(b"VN");
+        image[0x1C..0x20].copy_from_slice(b"NPDS");
+        image[0x30] = PciExpansionRomCodeType::NvidiaHDCP as u8;
+        let region = Region::read_at(&mut Cursor::new(&image), 0, &ParseOptions::default());
+
+        let Ok(Some(Region::HdcpImage(hdcp))) = region else {
+            panic!("Unexpected region {:?}", region);
+        };
+        assert_eq!("hdcp", hdcp.kind());
+        assert_eq!(512, hdcp.region_size());
+
+        image[0x30] = PciExpansionRomCodeType::NvidiaX86Extension as u8;
+        let region = Region::read_at(&mut Cursor::new(&image), 0, &ParseOptions::default());
+        assert!(matches!(region, Ok(Some(Region::NvidiaPciExpansionRom(_)))));
+    }
+
+    #[test]
+    fn test_scan_alignment() {
+        let mut bytes = vec![0u8; 256];
+        bytes.extend(legacy_image());
+        let mut cursor = Cursor::new(bytes);
+
+        assert!(RegionIterator::new(&mut cursor).next().is_none());
+
+        cursor.set_position(0);
+        let options = ParseOptions {
+            scan_alignment: 256,
+            ..ParseOptions::default()
+        };
+        let regions: Vec<Region> = RegionIterator::with_options(&mut cursor, options).collect();
+
+        assert_eq!(1, regions.len());
+        assert!(matches!(regions[0], Region::LegacyPciExpansionRom(_)));
+        assert_eq!(256, regions[0].offset_in_firmware());
+    }
+
+    #[test]
+    fn test_wrong_nvidia_pcir_offset() {
+        let mut cursor = Cursor::new(image_with_wrong_pcir_offset(b"VN", b"NPDS"));
+        assert!(Region::read_at(&mut cursor, 0, &ParseOptions::default())
+            .unwrap()
+            .is_none());
+
+        let result =
+            read_region_args::<NvidiaPciExpansionRom>(&mut cursor, 0, (DEFAULT_MAX_IMAGE_BYTES,));
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFormat(message)) if message == "NPDS signature not found at pcir_offset 0x40"
+        ));
+    }
+
+    #[test]
+    fn test_version_hex4_formats() {
+        let version = VersionHex4([0x01, 0x02, 0x03, 0x94]);
+
+        assert_eq!("94.03.02.01", version.to_string());
+        assert_eq!("01.02.03.94", version.forward());
+        assert_eq!(0x94030201, version.to_u32_le());
+        assert_eq!(version, VersionHex4::from_u32_le(0x94030201));
+        assert_eq!(&[0x01, 0x02, 0x03, 0x94], version.as_bytes());
+    }
+
+    #[test]
+    fn test_3060ti() {
+        init_logger();
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        println!("Firmware: {:#?}", &firmware_bundle);
+        println!("\n\n\n{:#?}", firmware_bundle.v_bios_info())
+    }
+
+    #[test]
+    fn test_3060ti_memory_clock() {
+        init_logger();
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        if let Some(memory_clock_table) = firmware_bundle
+            .firmwares
+            .first()
+            .and_then(|f| f.legacy_pci_image.as_ref())
+            .and_then(|i| i.memory_clock_table.as_ref())
+        {
+            println!("Memory clock table: {:?}", &memory_clock_table);
+            for entry in &memory_clock_table.entries {
+                println!("Entry: {:?}", entry.base_entry.unknown)
+            }
+        }
+    }
+
+    #[test]
+    fn test_3060ti_memory_clock_range() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let memory_clock_table = firmware_bundle
+            .firmwares
+            .first()
+            .and_then(|f| f.legacy_pci_image.as_ref())
+            .and_then(|i| i.memory_clock_table.as_ref())
+            .unwrap();
+        let max_min_freq = memory_clock_table
+            .entries
+            .iter()
+            .map(|e| e.base_entry.min_freq)
+            .max()
+            .unwrap();
+        assert!((1000..=10000).contains(&max_min_freq));
+        for entry in &memory_clock_table.entries {
+            assert!(entry.base_entry.max_freq <= 0x3FFF);
+            assert!(entry.base_entry.min_freq <= entry.base_entry.max_freq);
+        }
+    }
+
+    #[test]
+    fn test_3060ti_bit_tokens_versions() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let image = firmware_bundle
+            .firmwares
+            .first()
+            .and_then(|f| f.legacy_pci_image.as_ref())
+            .unwrap();
+
+        assert_eq!(image.bit_tokens_data.len(), image.bit_tokens_versions.len());
+        let perf_version = image
+            .bit_tokens_with_versions()
+            .find_map(|(version, token)| matches!(token, BITTokenType::Perf(_)).then_some(version));
+        assert_eq!(Some(2), perf_version);
+    }
+
+    #[test]
+    fn test_write_report() {
+        let mut bytes = legacy_image();
+        bytes.resize(1024, 0);
+        let mut open_firmware = legacy_image();
+        open_firmware[0x30] = PciExpansionRomCodeType::OpenFirmwareStandardForPci as u8;
+        bytes.extend(open_firmware);
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&bytes).unwrap();
+
+        let mut report = Vec::new();
+        firmware_bundle.write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert!(lines.contains(&"  0x00000000       512 legacy"));
+        assert!(lines.contains(&"  0x00000400       512 open-firmware"));
+        assert!(lines.contains(&"  0x00000200       512 gap"));
+        assert!(lines.contains(&"firmware 0:"));
+    }
+
+    #[test]
+    fn test_3060ti_data_range_table() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+
+        let table = image.data_range_table.as_ref().unwrap();
+        assert!(!table.ranges.is_empty());
+        assert!(table
+            .ranges
+            .iter()
+            .all(|range| range.end as usize <= image.image.data.len()));
+    }
+
+    #[test]
+    fn test_3060ti_table_statuses() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        let statuses = image.table_statuses();
+
+        assert!(statuses.contains(&("memory clock table", TableStatus::Present)));
+        for failure in &image.table_failures {
+            assert!(statuses
+                .iter()
+                .any(|(table, status)| *table == failure.table
+                    && *status == TableStatus::Failed(failure.error.clone())));
+        }
+    }
+
+    #[test]
+    fn test_table_statuses_include_pll_info() {
+        let firmware_bundle = FirmwareBundleInfo::parse_bytes(&legacy_image()).unwrap();
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        let statuses = image.table_statuses();
+
+        assert!(statuses.contains(&("PLL info", TableStatus::Absent)));
+        assert!(statuses
+            .iter()
+            .all(|(_, status)| *status == TableStatus::Absent));
+    }
+
+    #[test]
+    fn test_3060ti_quick_version() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let version = quick_version(&mut rom_file).unwrap();
+        rom_file.rewind().unwrap();
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+
+        assert_eq!(
+            Some(firmware_bundle.v_bios_info()[0].version.clone()),
+            version
+        );
+    }
+
+    #[test]
+    fn test_3060ti_field_map() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let field_map = firmware_bundle.field_map();
+        let memory_clock_table = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .and_then(|i| i.memory_clock_table.as_ref())
+            .unwrap();
+        let min_freq = field_map
+            .fields
+            .iter()
+            .find(|f| {
+                f.field_path
+                    == "firmwares[0].legacy_pci_image.memory_clock_table.entries[0].base_entry.min_freq"
+            })
+            .unwrap();
+        assert_eq!(2, min_freq.len);
+
+        let mut bytes = [0u8; 2];
+        rom_file.seek(SeekFrom::Start(min_freq.start)).unwrap();
+        rom_file.read_exact(&mut bytes).unwrap();
+        assert_eq!(
+            memory_clock_table.entries[0].base_entry.min_freq,
+            u16::from_le_bytes(bytes) & 0x3FFF
+        );
+    }
+
+    #[test]
+    fn test_3060ti_memory_tweak() {
+        init_logger();
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
         let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
         if let Some(memory_tweak_table) = firmware_bundle
             .firmwares
@@ -371,31 +1826,395 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_3060ti_postcard() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let postcard = firmware_bundle.to_postcard().unwrap();
+        let json = serde_json::to_vec(&firmware_bundle).unwrap();
+        assert!(!postcard.is_empty());
+        assert!(postcard.len() < json.len());
+        assert_eq!(postcard, firmware_bundle.to_postcard().unwrap());
+    }
+
     #[test]
     fn test_4090() {
-        TestLogger::init(LevelFilter::Debug, Config::default()).unwrap();
-        let mut rom_file = get_rom_file(
+        init_logger();
+        let Some(mut rom_file) = get_rom_file(
             "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
-        );
+        ) else {
+            return;
+        };
         let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
         println!("Firmware: {:#?}", &firmware_bundle);
         println!("\n\n\n{:#?}", firmware_bundle.v_bios_info())
     }
 
-    fn get_rom_file(url: &str) -> File {
-        let cache_dir = env::temp_dir().join(CACHE_FOLDER);
-        let url = Url::parse(url).unwrap();
-        let filename = url.path_segments().unwrap().last().unwrap();
-        let path = cache_dir.join(filename);
+    #[test]
+    fn test_4090_physical_connectors() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let connector_table = firmware_bundle
+            .firmwares
+            .first()
+            .and_then(|f| f.legacy_pci_image.as_ref())
+            .and_then(|i| i.connector_table.as_ref())
+            .unwrap();
+        let physical_connectors = connector_table.physical_connectors();
+        println!("Physical connectors: {:#?}", &physical_connectors);
+        assert!(!physical_connectors.is_empty());
+        assert!(physical_connectors
+            .iter()
+            .all(|c| !c.output_kinds.is_empty() && !c.connector_types.is_empty()));
+        assert!(physical_connectors
+            .iter()
+            .any(|c| c.output_kinds.contains(&ConnectorOutputKind::DisplayPort)));
+        assert!(physical_connectors
+            .iter()
+            .any(|c| c.output_kinds.contains(&ConnectorOutputKind::Hdmi)));
+    }
+
+    #[test]
+    fn test_4090_display_outputs() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let displays = firmware_bundle.display_outputs();
+
+        assert!(displays.display_port > 0);
+        assert!(displays.hdmi > 0);
+        assert!(displays.max_dp_link_rate_mbps.is_some());
+    }
+
+    #[test]
+    fn test_4090_structures_after_dcb() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_ref()
+            .unwrap();
+        assert!(image.bit_table_structure.is_some());
+        assert!(image.device_control_block.is_some());
+        for dcb in &image.additional_device_control_blocks {
+            assert_ne!(
+                image
+                    .device_control_block
+                    .as_ref()
+                    .unwrap()
+                    .offset_in_region,
+                dcb.offset_in_region
+            );
+        }
+    }
+
+    /// Returns at most one byte per read, like `ContinuousRegionReader` at region boundaries
+    struct ByteReader<R>(R);
 
-        if !cache_dir.exists() {
-            fs::create_dir(cache_dir).unwrap();
+    impl<R: Read> Read for ByteReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
         }
-        if !path.exists() {
-            let mut response = reqwest::blocking::get(url).unwrap();
-            let mut file = File::create(&path).unwrap();
-            response.copy_to(&mut file).unwrap();
+    }
+
+    impl<R: Seek> Seek for ByteReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_4090_structures_partial_reads() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let mut bytes = Vec::new();
+        rom_file.read_to_end(&mut bytes).unwrap();
+
+        let mut cursor = Cursor::new(&bytes);
+        let structures: Vec<String> = RegionStructureIterator::new(&mut cursor)
+            .map(|s| format!("{:?}", s))
+            .collect();
+        let mut byte_reader = ByteReader(Cursor::new(&bytes));
+        let byte_reader_structures: Vec<String> = RegionStructureIterator::new(&mut byte_reader)
+            .map(|s| format!("{:?}", s))
+            .collect();
+        assert!(!structures.is_empty());
+        assert_eq!(structures, byte_reader_structures);
+    }
+
+    #[test]
+    fn test_4090_summary() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let summary = firmware_bundle.summary();
+        assert_eq!(firmware_bundle.firmwares.len(), summary.firmware_count);
+        assert!(summary.pci_image_count > 0);
+        assert_eq!(Some(0x10DE), summary.vendor_id);
+        assert!(summary.vbios_version.is_some());
+        assert!(summary.parsed_extent <= rom_file.metadata().unwrap().len());
+    }
+
+    #[test]
+    fn test_parse_bytes_untrusted() {
+        assert!(FirmwareBundleInfo::parse_bytes(&[]).is_ok());
+        let garbage: Vec<u8> = (0..4096u32).map(|i| (i * 31 % 251) as u8).collect();
+        let _ = FirmwareBundleInfo::parse_bytes(&garbage);
+
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let mut bytes = Vec::new();
+        rom_file.read_to_end(&mut bytes).unwrap();
+        for len in (0..bytes.len()).step_by(bytes.len() / 16 + 1) {
+            let _ = FirmwareBundleInfo::parse_bytes(&bytes[..len]);
+        }
+        for index in (0..bytes.len()).step_by(bytes.len() / 16 + 1) {
+            let mut corrupted = bytes.clone();
+            corrupted[index] = !corrupted[index];
+            let _ = FirmwareBundleInfo::parse_bytes(&corrupted);
+        }
+    }
+
+    #[test]
+    fn test_v_bios_info_without_data_header_extended() {
+        let firmware_bundle = FirmwareBundleInfo {
+            firmwares: vec![FirmwareInfo::default()],
+            ..FirmwareBundleInfo::default()
+        };
+        let info = firmware_bundle.v_bios_info();
+        assert_eq!(1, info.len());
+        assert_eq!("N/A", info[0].version);
+        assert!(info[0].gop_version.is_none());
+        assert!(info[0].subsystem_id.is_none());
+    }
+
+    #[test]
+    fn test_partner_customized() {
+        assert!(!FirmwareBundleInfo::default().is_partner_customized());
+        let mut firmware_bundle = FirmwareBundleInfo::parse_bytes(&legacy_image()).unwrap();
+        assert!(!firmware_bundle.is_partner_customized());
+
+        let mut bios: BiosDataToken = Cursor::new(vec![0u8; 64]).read_le().unwrap();
+        bios.applied_rom_pack_count = 1;
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_mut()
+            .unwrap();
+        image.bit_tokens_data.push(BITTokenType::Bios(bios));
+        assert!(firmware_bundle.is_partner_customized());
+
+        let mut firmware_bundle = FirmwareBundleInfo::parse_bytes(&legacy_image()).unwrap();
+        let mut extended = Vec::from(&b"NPDE"[..]);
+        extended.extend_from_slice(&[0, 0, 12, 0, 1, 0, 0x80]);
+        extended.push(NvidiaPciDataExtendedFlags::PrivateImagesEnabled.bits());
+        let image = firmware_bundle.firmwares[0]
+            .legacy_pci_image
+            .as_mut()
+            .unwrap();
+        image.image.data_header_extended = Some(Cursor::new(extended).read_le().unwrap());
+        assert!(firmware_bundle.is_partner_customized());
+    }
+
+    #[test]
+    fn test_4090_nvgi_version() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let nvgi_regions: Vec<_> = firmware_bundle
+            .firmwares
+            .iter()
+            .flat_map(|firmware| &firmware.nvgi_regions)
+            .collect();
+
+        assert!(!nvgi_regions.is_empty());
+        assert!(nvgi_regions
+            .iter()
+            .all(|nvgi| nvgi.version() == nvgi_regions[0].version()));
+    }
+
+    #[test]
+    fn test_parse_all_concatenated_roms() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let Some(mut second_rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/236055/MSI.RTX3060Ti.8192.201112.rom",
+        ) else {
+            return;
+        };
+        let mut bytes = Vec::new();
+        rom_file.read_to_end(&mut bytes).unwrap();
+        let first_rom_len = bytes.len() as u64;
+        second_rom_file.read_to_end(&mut bytes).unwrap();
+        rom_file.rewind().unwrap();
+        second_rom_file.rewind().unwrap();
+        let first = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let second = FirmwareBundleInfo::parse(&mut second_rom_file).unwrap();
+
+        let firmware_bundles =
+            FirmwareBundleInfo::parse_all(&mut Cursor::new(bytes), &ParseOptions::default())
+                .unwrap();
+
+        assert_eq!(2, firmware_bundles.len());
+        assert_eq!(first.firmwares.len(), firmware_bundles[0].firmwares.len());
+        assert_eq!(second.firmwares.len(), firmware_bundles[1].firmwares.len());
+        assert!(firmware_bundles[1]
+            .all_regions()
+            .iter()
+            .all(|region| region.offset_in_firmware() >= first_rom_len));
+    }
+
+    #[test]
+    fn test_4090_regions_sorted() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let options = ParseOptions {
+            retain_regions: true,
+            ..ParseOptions::default()
+        };
+        let firmware_bundle =
+            FirmwareBundleInfo::parse_with_options(&mut rom_file, &options).unwrap();
+        let regions = firmware_bundle.regions_sorted();
+        assert!(regions.len() >= firmware_bundle.all_regions().len());
+        assert!(regions
+            .windows(2)
+            .all(|pair| pair[0].offset_in_firmware() < pair[1].offset_in_firmware()));
+
+        rom_file.rewind().unwrap();
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        assert!(firmware_bundle.regions_sorted().is_empty());
+    }
+
+    #[test]
+    fn test_4090_has_ifr_container() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        assert!(firmware_bundle.has_ifr_container());
+    }
+
+    #[test]
+    fn test_4090_efi_architectures() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        assert_eq!(
+            vec![EfiPciExpansionRomMachineType::X64],
+            firmware_bundle.efi_architectures()
+        );
+    }
+
+    #[test]
+    fn test_4090_region_firmware_index() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let options = ParseOptions {
+            retain_regions: true,
+            ..ParseOptions::default()
+        };
+        let firmware_bundle =
+            FirmwareBundleInfo::parse_with_options(&mut rom_file, &options).unwrap();
+        assert!(firmware_bundle.firmwares.len() > 1);
+        assert_eq!(
+            firmware_bundle.regions.len(),
+            firmware_bundle.region_firmware_indexes.len()
+        );
+
+        let mut firmware_indexes = Vec::new();
+        for (region, firmware_index) in firmware_bundle.regions_with_firmware_index() {
+            if let Region::NbsiPciExpansionRom(_) = region {
+                assert_eq!(None, firmware_index);
+                continue;
+            }
+            assert_eq!(firmware_bundle.firmware_index_of(region), firmware_index);
+            firmware_indexes.extend(firmware_index);
         }
-        File::open(&path).unwrap()
+        assert!(firmware_indexes.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(
+            Some(firmware_bundle.firmwares.len() - 1),
+            firmware_indexes.last().copied()
+        );
+    }
+
+    #[test]
+    fn test_4090_parse_streaming() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let mut offsets = Vec::new();
+        FirmwareBundleInfo::parse_streaming(&mut rom_file, |region| {
+            offsets.push(region.offset_in_firmware())
+        })
+        .unwrap();
+        rom_file.seek(SeekFrom::Start(0)).unwrap();
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let expected_offsets: Vec<u64> = firmware_bundle
+            .all_regions()
+            .iter()
+            .map(|r| r.offset_in_firmware())
+            .collect();
+        assert_eq!(expected_offsets, offsets);
+    }
+
+    #[test]
+    fn test_4090_all_regions() {
+        let Some(mut rom_file) = get_rom_file(
+            "https://www.techpowerup.com/vgabios/260748/Asus.RTX4090.24576.230321.rom",
+        ) else {
+            return;
+        };
+        let region_count = RegionIterator::new(&mut rom_file).count();
+        rom_file.seek(SeekFrom::Start(0)).unwrap();
+        let firmware_bundle = FirmwareBundleInfo::parse(&mut rom_file).unwrap();
+        let regions = firmware_bundle.all_regions();
+        assert_eq!(region_count, regions.len());
+        assert!(regions
+            .windows(2)
+            .all(|pair| pair[0].offset_in_firmware() <= pair[1].offset_in_firmware()));
     }
 }
@@ -1,57 +1,894 @@
 // SPDX-License-Identifier: MIT
 
 use clap::{Parser, ValueEnum};
-use nv_rom_parser::firmware::FirmwareBundleInfo;
+use nv_rom_parser::cursor::{BufferedSeekable, ContinuousRegionReader, OffsetReader};
+use nv_rom_parser::firmware::{FirmwareBundleInfo, FirmwareInfo, LegacyPciImageInfo, TableStatus};
+use nv_rom_parser::nvidia::bit::dfp::FlatPanelTable;
+use nv_rom_parser::nvidia::bit::mxm::{MxmAuxToCcbTable, MxmDigitalConnectorTable};
+use nv_rom_parser::nvidia::bit::nvinit::{
+    ConditionTable, InitFunctionTable, MacroIndexTable, MacroTable, CONDITION_ENTRY_SIZE,
+    INIT_FUNCTION_ENTRY_SIZE, MACRO_ENTRY_SIZE, MACRO_INDEX_ENTRY_SIZE,
+};
+use nv_rom_parser::nvidia::bit::nvlink::NvLinkConfigData;
+use nv_rom_parser::nvidia::bit::perf::{
+    MemoryClockTable, MemoryTweakTable, PowerPolicyTable, RawTable, ThermalChannelTable,
+    ThermalPolicyTable, VirtualPStateTable20,
+};
+use nv_rom_parser::nvidia::bit::{BITStructure, BITTokenType, PllInfo, StringToken};
+use nv_rom_parser::nvidia::dcb::{
+    CommunicationsControlBlock, ConnectorTable, DeviceControlBlock, GpioAssignmentTable,
+    I2cDevicesTable,
+};
+use nv_rom_parser::nvidia::nbsi::NbsiPciExpansionRom;
+use nv_rom_parser::nvidia::{NvgiRegion, NvidiaPciExpansionRom, RfrdRegion};
+use nv_rom_parser::pci_efi::EfiPciExpansionRom;
+use nv_rom_parser::pci_legacy::PciExpansionRom;
+use nv_rom_parser::validate::{CheckResult, ChecksumFix};
+use nv_rom_parser::{FirmwareRegion, ParseOptions, Region};
+use serde::Serialize;
+use std::fs;
 use std::fs::File;
-use std::path::PathBuf;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const MAX_STDIN_BYTES: u64 = 64 * 1024 * 1024;
+/// Base name of the unpacked files for a ROM read from the standard input
+const STDIN_ROM_FILE: &str = "stdin.rom";
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    rom_file: PathBuf,
+    /// ROM file, the ROM is read from the standard input when omitted
+    rom_file: Option<PathBuf>,
 
     #[arg(short, long, value_enum, default_value_t = Command::VBios)]
     command: Command,
 
     #[arg(short, long, value_enum, default_value_t = Output::Debug)]
     output: Output,
+
+    /// Comma-separated list of structures to print for the full command
+    #[arg(long, value_enum, value_delimiter = ',')]
+    only: Vec<Kind>,
+
+    /// Print a hex dump of the bytes backing each structure selected with --only
+    #[arg(long)]
+    raw_hex: bool,
+
+    /// Print the JSON output on a single line instead of pretty-printing it
+    #[arg(long)]
+    json_compact: bool,
+
+    /// Output file of the fix-checksum command, `<basename>.fixed.rom` next to the ROM by default
+    #[arg(long)]
+    out_file: Option<PathBuf>,
+
+    /// Parse a single region at the hex offset, counted past any preamble, instead of scanning
+    /// the whole file
+    #[arg(long, value_parser = parse_hex_offset)]
+    offset: Option<u64>,
+}
+
+fn parse_hex_offset(value: &str) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16).map_err(|err| format!("Invalid hex offset {}: {}", value, err))
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Command {
     VBios,
     Full,
+    Unpack,
+    /// Runs the checksum, image chain and layout checks, exits with a nonzero status on failure
+    Validate,
+    /// Lists the optional tables of each legacy image as present, absent or failed to parse
+    ListTables,
+    /// Counts the display outputs by kind and prints the maximum DisplayPort link rate
+    Displays,
+    /// Rewrites the PCI image checksums, writes the fixed ROM and validates it
+    FixChecksum,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Output {
     Debug,
     Json,
+    /// Plain text map of the parsed layout for bug reports
+    Report,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Kind {
+    Nvgi,
+    Rfrd,
+    Legacy,
+    Efi,
+    NvidiaPci,
+    OpenFirmware,
+    Hdcp,
+    Nbsi,
+    Bit,
+    BitTokens,
+    String,
+    Nvlink,
+    MacroIndex,
+    Macro,
+    Condition,
+    InitFunction,
+    MemoryClock,
+    MemoryTweak,
+    Pll,
+    PowerPolicy,
+    VirtualPState,
+    ThermalChannel,
+    ThermalPolicy,
+    Didt,
+    Ventura,
+    FlatPanel,
+    Mxm,
+    Dcb,
+    Gpio,
+    I2c,
+    Connector,
+    Ccb,
+}
+
+#[derive(Debug, Serialize)]
+struct FilteredBundle<'a> {
+    firmwares: Vec<Vec<Structure<'a>>>,
+    nbsi_pci_expansion_rom: Option<&'a NbsiPciExpansionRom>,
+}
+
+#[derive(Debug, Serialize)]
+enum Structure<'a> {
+    Nvgi(&'a NvgiRegion),
+    Rfrd(&'a RfrdRegion),
+    Legacy(&'a PciExpansionRom),
+    Efi(&'a EfiPciExpansionRom),
+    NvidiaPci(&'a NvidiaPciExpansionRom),
+    OpenFirmware(&'a PciExpansionRom),
+    Hdcp(&'a NvidiaPciExpansionRom),
+    Bit(&'a BITStructure),
+    BitTokens(&'a Vec<BITTokenType>),
+    String(&'a StringToken),
+    Nvlink(&'a NvLinkConfigData),
+    MacroIndex(&'a MacroIndexTable),
+    Macro(&'a MacroTable),
+    Condition(&'a ConditionTable),
+    InitFunction(&'a InitFunctionTable),
+    MemoryClock(&'a MemoryClockTable),
+    MemoryTweak(&'a MemoryTweakTable),
+    Pll(&'a PllInfo),
+    PowerPolicy(&'a PowerPolicyTable),
+    VirtualPState(&'a VirtualPStateTable20),
+    ThermalChannel(&'a ThermalChannelTable),
+    ThermalPolicy(&'a ThermalPolicyTable),
+    Didt(&'a RawTable),
+    Ventura(&'a RawTable),
+    FlatPanel(&'a FlatPanelTable),
+    MxmDigitalConnector(&'a MxmDigitalConnectorTable),
+    MxmAuxToCcb(&'a MxmAuxToCcbTable),
+    Dcb(&'a DeviceControlBlock),
+    Gpio(&'a GpioAssignmentTable),
+    I2c(&'a I2cDevicesTable),
+    Connector(&'a ConnectorTable),
+    Ccb(&'a CommunicationsControlBlock),
+}
+
+fn filter_bundle<'a>(bundle: &'a FirmwareBundleInfo, kinds: &[Kind]) -> FilteredBundle<'a> {
+    FilteredBundle {
+        firmwares: bundle
+            .firmwares
+            .iter()
+            .map(|firmware| filter_firmware(firmware, kinds))
+            .collect(),
+        nbsi_pci_expansion_rom: bundle
+            .nbsi_pci_expansion_rom
+            .as_ref()
+            .filter(|_| kinds.contains(&Kind::Nbsi)),
+    }
+}
+
+fn filter_firmware<'a>(firmware: &'a FirmwareInfo, kinds: &[Kind]) -> Vec<Structure<'a>> {
+    let mut structures = Vec::new();
+    for kind in kinds {
+        match kind {
+            Kind::Nvgi => structures.extend(firmware.nvgi_regions.iter().map(Structure::Nvgi)),
+            Kind::Rfrd => structures.extend(firmware.rfrd_region.as_ref().map(Structure::Rfrd)),
+            Kind::Efi => structures.extend(firmware.efi_pci_images().map(Structure::Efi)),
+            Kind::NvidiaPci => structures.extend(
+                firmware
+                    .nv_pci_expansion_roms
+                    .iter()
+                    .map(Structure::NvidiaPci),
+            ),
+            Kind::OpenFirmware => structures.extend(
+                firmware
+                    .open_firmware_images
+                    .iter()
+                    .map(Structure::OpenFirmware),
+            ),
+            Kind::Hdcp => structures.extend(firmware.hdcp_images.iter().map(Structure::Hdcp)),
+            Kind::Nbsi => {}
+            Kind::Mxm => {
+                if let Some(image) = &firmware.legacy_pci_image {
+                    structures.extend(
+                        image
+                            .mxm_digital_connector_table
+                            .as_ref()
+                            .map(Structure::MxmDigitalConnector),
+                    );
+                    structures.extend(
+                        image
+                            .mxm_aux_to_ccb_table
+                            .as_ref()
+                            .map(Structure::MxmAuxToCcb),
+                    );
+                }
+            }
+            _ => {
+                if let Some(image) = &firmware.legacy_pci_image {
+                    structures.extend(match kind {
+                        Kind::Legacy => Some(Structure::Legacy(&image.image)),
+                        Kind::Bit => image.bit_table_structure.as_ref().map(Structure::Bit),
+                        Kind::BitTokens => Some(Structure::BitTokens(&image.bit_tokens_data)),
+                        Kind::String => image.bit_string_token.as_ref().map(Structure::String),
+                        Kind::Nvlink => image.nvlink_config_data.as_ref().map(Structure::Nvlink),
+                        Kind::MacroIndex => {
+                            image.macro_index_table.as_ref().map(Structure::MacroIndex)
+                        }
+                        Kind::Macro => image.macro_table.as_ref().map(Structure::Macro),
+                        Kind::Condition => image.condition_table.as_ref().map(Structure::Condition),
+                        Kind::InitFunction => image
+                            .init_function_table
+                            .as_ref()
+                            .map(Structure::InitFunction),
+                        Kind::MemoryClock => image
+                            .memory_clock_table
+                            .as_ref()
+                            .map(Structure::MemoryClock),
+                        Kind::MemoryTweak => image
+                            .memory_tweak_table
+                            .as_ref()
+                            .map(Structure::MemoryTweak),
+                        Kind::Pll => image.pll_info.as_ref().map(Structure::Pll),
+                        Kind::PowerPolicy => image
+                            .power_policy_table
+                            .as_ref()
+                            .map(Structure::PowerPolicy),
+                        Kind::VirtualPState => image
+                            .virtual_p_state_table
+                            .as_ref()
+                            .map(Structure::VirtualPState),
+                        Kind::ThermalChannel => image
+                            .thermal_channel_table
+                            .as_ref()
+                            .map(Structure::ThermalChannel),
+                        Kind::ThermalPolicy => image
+                            .thermal_policy_table
+                            .as_ref()
+                            .map(Structure::ThermalPolicy),
+                        Kind::Didt => image.didt_table.as_ref().map(Structure::Didt),
+                        Kind::Ventura => image.ventura_table.as_ref().map(Structure::Ventura),
+                        Kind::FlatPanel => {
+                            image.flat_panel_table.as_ref().map(Structure::FlatPanel)
+                        }
+                        Kind::Dcb => image.device_control_block.as_ref().map(Structure::Dcb),
+                        Kind::Gpio => image.gpio_assignment_table.as_ref().map(Structure::Gpio),
+                        Kind::I2c => image.i2c_devices_table.as_ref().map(Structure::I2c),
+                        Kind::Connector => image.connector_table.as_ref().map(Structure::Connector),
+                        Kind::Ccb => image
+                            .communications_control_block
+                            .as_ref()
+                            .map(Structure::Ccb),
+                        _ => None,
+                    });
+                }
+            }
+        }
+    }
+    structures
+}
+
+enum RawRange {
+    Firmware(Range<u64>),
+    Image(Range<u64>),
+}
+
+fn table_size(header_size: u8, entry_count: u8, entry_size: u64) -> u64 {
+    header_size as u64 + entry_count as u64 * entry_size
+}
+
+fn raw_range(structure: &Structure, image: Option<&LegacyPciImageInfo>) -> Option<RawRange> {
+    let firmware_range =
+        |region: &dyn FirmwareRegion| Some(RawRange::Firmware(region.firmware_range()));
+    let image_range = |start: u64, size: u64| Some(RawRange::Image(start..start + size));
+    let perf_ptrs = image.and_then(|image| {
+        image.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::Perf(ptrs) => Some(*ptrs),
+            _ => None,
+        })
+    });
+    let nvinit_ptrs = image.and_then(|image| {
+        image.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::NvInit(ptrs) => Some(*ptrs),
+            _ => None,
+        })
+    });
+    let dfp_ptrs = image.and_then(|image| {
+        image.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::Dfp(ptrs) => Some(*ptrs),
+            _ => None,
+        })
+    });
+    let mxm_token = image.and_then(|image| {
+        image.bit_tokens_data.iter().find_map(|token| match token {
+            BITTokenType::Mxm(mxm) => Some(mxm),
+            _ => None,
+        })
+    });
+    let dcb_header = image
+        .and_then(|image| image.device_control_block.as_ref())
+        .map(|dcb| &dcb.header);
+
+    match structure {
+        Structure::Nvgi(region) => firmware_range(*region),
+        Structure::Rfrd(region) => firmware_range(*region),
+        Structure::Legacy(region) => firmware_range(*region),
+        Structure::Efi(region) => firmware_range(*region),
+        Structure::NvidiaPci(region) => firmware_range(*region),
+        Structure::OpenFirmware(region) => firmware_range(*region),
+        Structure::Hdcp(region) => firmware_range(*region),
+        Structure::Bit(bit) => image_range(
+            bit.offset_in_region,
+            bit.header.header_size as u64
+                + bit.header.token_entries as u64 * bit.header.token_size as u64,
+        ),
+        Structure::Dcb(dcb) => image_range(
+            dcb.offset_in_region,
+            table_size(
+                dcb.header.header_size,
+                dcb.header.entry_count,
+                dcb.header.entry_size as u64,
+            ),
+        ),
+        Structure::Gpio(table) => image_range(
+            dcb_header?.gpio_assignment_table_pointer as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::I2c(table) => image_range(
+            dcb_header?.i2c_devices_table_pointer as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::Connector(table) => image_range(
+            dcb_header?.connector_table_pointer as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::Ccb(table) => image_range(
+            dcb_header?.communications_control_block_pointer as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::MemoryClock(table) => image_range(
+            perf_ptrs?.memory_clock_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.base_entry_size as u64
+                    + table.header.strap_entry_count as u64 * table.header.strap_entry_size as u64,
+            ),
+        ),
+        Structure::MemoryTweak(table) => image_range(
+            perf_ptrs?.memory_tweak_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.base_entry_size as u64
+                    + table.header.extended_entry_count as u64
+                        * table.header.extended_entry_size as u64,
+            ),
+        ),
+        Structure::PowerPolicy(table) => image_range(
+            perf_ptrs?.power_policy_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::VirtualPState(table) => image_range(
+            perf_ptrs?.virtual_p_state_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.base_entry_size as u64
+                    + table.header.domain_freq_entry_count as u64
+                        * table.header.domain_freq_entry_size as u64,
+            ),
+        ),
+        Structure::ThermalChannel(table) => image_range(
+            perf_ptrs?.thermal_channel_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::ThermalPolicy(table) => image_range(
+            perf_ptrs?.thermal_policy_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::Didt(table) => image_range(
+            perf_ptrs?.didt_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::Ventura(table) => image_range(
+            perf_ptrs?.ventura_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::MacroIndex(table) => image_range(
+            nvinit_ptrs?.macro_index_table_ptr as u64,
+            table.entries.len() as u64 * MACRO_INDEX_ENTRY_SIZE,
+        ),
+        Structure::Macro(table) => image_range(
+            nvinit_ptrs?.macro_table_ptr as u64,
+            table.entries.len() as u64 * MACRO_ENTRY_SIZE,
+        ),
+        Structure::Condition(table) => image_range(
+            nvinit_ptrs?.condition_table_ptr as u64,
+            table.entries.len() as u64 * CONDITION_ENTRY_SIZE,
+        ),
+        Structure::InitFunction(table) => image_range(
+            nvinit_ptrs?.init_function_table_ptr as u64,
+            table.entries.len() as u64 * INIT_FUNCTION_ENTRY_SIZE,
+        ),
+        Structure::FlatPanel(table) => image_range(
+            dfp_ptrs?.fp_table_ptr as u64,
+            table_size(
+                table.header.header_size,
+                table.header.entry_count,
+                table.header.entry_size as u64,
+            ),
+        ),
+        Structure::MxmDigitalConnector(table) => image_range(
+            mxm_token?.mxm_digital_connector_table_ptr as u64,
+            table_size(table.header.header_size, table.header.entry_count, 1),
+        ),
+        Structure::MxmAuxToCcb(table) => image_range(
+            mxm_token?.mxm_aux_to_ccb_table_ptr as u64,
+            table_size(table.header.header_size, table.header.entry_count, 1),
+        ),
+        _ => None,
+    }
+}
+
+fn read_raw_bytes<S: Read + Seek>(
+    file: &mut S,
+    firmware: &FirmwareInfo,
+    raw_range: &RawRange,
+) -> std::io::Result<(u64, Vec<u8>)> {
+    match raw_range {
+        RawRange::Firmware(range) => {
+            let mut bytes = vec![0u8; (range.end - range.start) as usize];
+            file.seek(SeekFrom::Start(range.start))?;
+            file.read_exact(&mut bytes)?;
+            Ok((range.start, bytes))
+        }
+        RawRange::Image(range) => {
+            let mut bytes = vec![0u8; (range.end - range.start) as usize];
+            let mut regions: Vec<&dyn FirmwareRegion> = Vec::new();
+            if let Some(image) = &firmware.legacy_pci_image {
+                regions.push(&image.image);
+            }
+            for nv in &firmware.nv_pci_expansion_roms {
+                regions.push(nv);
+            }
+            let mut reader = ContinuousRegionReader::new(file, regions);
+            reader.seek(SeekFrom::Start(range.start))?;
+            reader.read_exact(&mut bytes)?;
+            Ok((range.start, bytes))
+        }
+    }
+}
+
+fn print_hex_dump(start: u64, bytes: &[u8]) {
+    for (index, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!(
+            "{:08X}  {:<47}  |{}|",
+            start + index as u64 * 16,
+            hex.join(" "),
+            ascii
+        );
+    }
+}
+
+fn print_raw_hex<S: Read + Seek>(
+    file: &mut S,
+    bundle: &FirmwareBundleInfo,
+    filtered_bundle: &FilteredBundle,
+) {
+    for (firmware, structures) in bundle.firmwares.iter().zip(&filtered_bundle.firmwares) {
+        for structure in structures {
+            println!("{:#?}", structure);
+            match raw_range(structure, firmware.legacy_pci_image.as_ref()) {
+                Some(range) => match read_raw_bytes(file, firmware, &range) {
+                    Ok((start, bytes)) => print_hex_dump(start, &bytes),
+                    Err(err) => println!("Cannot read raw bytes: {}", err),
+                },
+                None => println!("Raw bytes are not available for this structure"),
+            }
+        }
+    }
+    if let Some(nbsi) = filtered_bundle.nbsi_pci_expansion_rom {
+        println!("{:#?}", nbsi);
+        let range = RawRange::Firmware(nbsi.firmware_range());
+        match read_raw_bytes(file, &FirmwareInfo::default(), &range) {
+            Ok((start, bytes)) => print_hex_dump(start, &bytes),
+            Err(err) => println!("Cannot read raw bytes: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct UnpackedRegion {
+    index: usize,
+    kind: &'static str,
+    offset: u64,
+    size: u64,
+    path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct FixedRom {
+    path: PathBuf,
+    fixes: Vec<ChecksumFix>,
+    results: Vec<CheckResult>,
+}
+
+fn fix_checksums<S: Read + Seek>(
+    file: &mut S,
+    out_file: &Path,
+    bundle: &FirmwareBundleInfo,
+) -> nv_rom_parser::Result<FixedRom> {
+    let mut bytes = Vec::new();
+    file.rewind()?;
+    file.read_to_end(&mut bytes)?;
+    let fixes = bundle.fix_checksums(&mut bytes)?;
+    fs::write(out_file, &bytes)?;
+    let results = FirmwareBundleInfo::parse_bytes(&bytes)?.validate(&mut Cursor::new(&bytes))?;
+    Ok(FixedRom {
+        path: out_file.to_path_buf(),
+        fixes,
+        results,
+    })
+}
+
+fn print_check_results(results: &[CheckResult]) {
+    for result in results {
+        match (&result.error, result.offset) {
+            (None, _) => println!("PASS {}", result.check),
+            (Some(error), Some(offset)) => {
+                println!("FAIL {} at {:#010X}: {}", result.check, offset, error)
+            }
+            (Some(error), None) => println!("FAIL {}: {}", result.check, error),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TableListing {
+    firmware: usize,
+    table: &'static str,
+    status: TableStatus,
+}
+
+fn list_tables(bundle: &FirmwareBundleInfo) -> Vec<TableListing> {
+    bundle
+        .firmwares
+        .iter()
+        .enumerate()
+        .filter_map(|(firmware, f)| Some((firmware, f.legacy_pci_image.as_ref()?)))
+        .flat_map(|(firmware, image)| {
+            image
+                .table_statuses()
+                .into_iter()
+                .map(move |(table, status)| TableListing {
+                    firmware,
+                    table,
+                    status,
+                })
+        })
+        .collect()
+}
+
+fn print_json<T: Serialize>(value: &T, compact: bool, what: &str) {
+    let json = if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    };
+    let json = json.unwrap_or_else(|_| {
+        panic!(
+            "Cannot serialize {} into JSON, try another output format",
+            what
+        )
+    });
+    println!("{}", json);
+}
+
+fn print_report(bundle: &FirmwareBundleInfo) {
+    bundle
+        .write_report(&mut std::io::stdout().lock())
+        .expect("Cannot write firmware report");
+}
+
+fn bundle_regions(bundle: &FirmwareBundleInfo) -> Vec<&dyn FirmwareRegion> {
+    let mut regions = bundle.all_regions();
+    regions.sort_by_key(|region| region.offset_in_firmware());
+    regions
+}
+
+/// Writes every region into `<basename>.<index>.<kind>.bin` next to the ROM file
+fn unpack<S: Read + Seek>(
+    file: &mut S,
+    rom_file: &Path,
+    bundle: &FirmwareBundleInfo,
+) -> std::io::Result<Vec<UnpackedRegion>> {
+    let basename = rom_file.file_stem().unwrap_or_default().to_string_lossy();
+    let file_len = file.seek(SeekFrom::End(0))?;
+    let mut unpacked = Vec::new();
+    for (index, region) in bundle_regions(bundle).into_iter().enumerate() {
+        let kind = region.kind();
+        let start = region.offset_in_firmware();
+        let end = region.end_offset_in_firmware().min(file_len);
+        let (offset, bytes) = read_raw_bytes(
+            file,
+            &FirmwareInfo::default(),
+            &RawRange::Firmware(start..end),
+        )?;
+        let path = rom_file.with_file_name(format!("{}.{}.{}.bin", basename, index, kind));
+        fs::write(&path, &bytes)?;
+        unpacked.push(UnpackedRegion {
+            index,
+            kind,
+            offset,
+            size: bytes.len() as u64,
+            path,
+        });
+    }
+    Ok(unpacked)
 }
 
 pub fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
     let args = Args::parse();
-    let mut file = File::open(&args.rom_file)
-        .expect(format!("Cannot open ROM file at {:?}", args.rom_file).as_str());
-    let firmware_bundle_info = FirmwareBundleInfo::parse(&mut file).unwrap();
+    match &args.rom_file {
+        Some(rom_file) => {
+            let mut file = File::open(rom_file)
+                .expect(format!("Cannot open ROM file at {:?}", rom_file).as_str());
+            run(&args, &mut file, rom_file);
+        }
+        None => {
+            let mut stdin = BufferedSeekable::new(std::io::stdin().lock(), MAX_STDIN_BYTES)
+                .expect("Cannot read ROM from the standard input");
+            run(&args, &mut stdin, Path::new(STDIN_ROM_FILE));
+        }
+    }
+}
+
+fn run<S: Read + Seek>(args: &Args, file: &mut S, rom_file: &Path) {
+    if let Some(offset) = args.offset {
+        let options = ParseOptions::default();
+        let preamble_len = FirmwareBundleInfo::detect_preamble_len(file, &options).unwrap();
+        let mut rom = OffsetReader::new(file, preamble_len);
+        let region = Region::read_at(&mut rom, offset, &options)
+            .unwrap()
+            .unwrap_or_else(|| panic!("No region found at {:#X}", offset));
+        match &args.output {
+            Output::Debug | Output::Report => {
+                println!("{:#?}", region);
+            }
+            Output::Json => {
+                print_json(&region, args.json_compact, "region");
+            }
+        }
+        return;
+    }
+
+    let firmware_bundle_info = FirmwareBundleInfo::parse(file).unwrap();
+    let mut rom = OffsetReader::new(file, firmware_bundle_info.preamble_len);
 
     match &args.command {
         Command::VBios => match &args.output {
-            Output::Debug => {
+            Output::Debug | Output::Report => {
                 println!("{:#?}", firmware_bundle_info.v_bios_info());
             }
             Output::Json => {
-                println!("{}", serde_json::to_string_pretty(&firmware_bundle_info.v_bios_info()).expect("Cannot serialize firmware bundle info into JSON, try another output format"));
+                print_json(
+                    &firmware_bundle_info.v_bios_info(),
+                    args.json_compact,
+                    "firmware bundle info",
+                );
             }
         },
+        Command::Full if !args.only.is_empty() => {
+            let filtered_bundle = filter_bundle(&firmware_bundle_info, &args.only);
+            match &args.output {
+                Output::Debug if args.raw_hex => {
+                    print_raw_hex(&mut rom, &firmware_bundle_info, &filtered_bundle);
+                }
+                Output::Debug => {
+                    println!("{:#?}", filtered_bundle);
+                }
+                Output::Report => {
+                    print_report(&firmware_bundle_info);
+                }
+                Output::Json => {
+                    print_json(&filtered_bundle, args.json_compact, "firmware bundle info");
+                }
+            }
+        }
         Command::Full => match &args.output {
             Output::Debug => {
                 println!("{:#?}", firmware_bundle_info);
             }
+            Output::Report => {
+                print_report(&firmware_bundle_info);
+            }
             Output::Json => {
-                println!("{}", serde_json::to_string_pretty(&firmware_bundle_info).expect("Cannot serialize firmware bundle info into JSON, try another output format"));
+                print_json(
+                    &firmware_bundle_info,
+                    args.json_compact,
+                    "firmware bundle info",
+                );
             }
         },
+        Command::Unpack => {
+            let unpacked =
+                unpack(&mut rom, rom_file, &firmware_bundle_info).expect("Cannot unpack regions");
+            match &args.output {
+                Output::Debug | Output::Report => {
+                    for region in &unpacked {
+                        println!(
+                            "{:>3} {:<10} {:#010X} {:>9} {}",
+                            region.index,
+                            region.kind,
+                            region.offset,
+                            region.size,
+                            region.path.display()
+                        );
+                    }
+                }
+                Output::Json => {
+                    print_json(&unpacked, args.json_compact, "unpacked regions");
+                }
+            }
+        }
+        Command::ListTables => {
+            let tables = list_tables(&firmware_bundle_info);
+            match &args.output {
+                Output::Debug | Output::Report => {
+                    for table in &tables {
+                        match &table.status {
+                            TableStatus::Present => {
+                                println!("[x] {} {}", table.firmware, table.table)
+                            }
+                            TableStatus::Absent => {
+                                println!("[ ] {} {}", table.firmware, table.table)
+                            }
+                            TableStatus::Failed(error) => {
+                                println!("[!] {} {}: {}", table.firmware, table.table, error)
+                            }
+                        }
+                    }
+                }
+                Output::Json => {
+                    print_json(&tables, args.json_compact, "table list");
+                }
+            }
+        }
+        Command::Displays => {
+            let displays = firmware_bundle_info.display_outputs();
+            match &args.output {
+                Output::Debug | Output::Report => {
+                    println!("DisplayPort: {}", displays.display_port);
+                    println!("HDMI: {}", displays.hdmi);
+                    println!("DVI: {}", displays.dvi);
+                    println!("VGA: {}", displays.vga);
+                    println!("Internal Panel: {}", displays.internal_panel);
+                    match displays.max_dp_link_rate_mbps {
+                        Some(rate) => println!("Max DisplayPort link rate: {} Mbps", rate),
+                        None => println!("Max DisplayPort link rate: N/A"),
+                    }
+                }
+                Output::Json => {
+                    print_json(&displays, args.json_compact, "display outputs");
+                }
+            }
+        }
+        Command::FixChecksum => {
+            let out_file = args.out_file.clone().unwrap_or_else(|| {
+                let basename = rom_file.file_stem().unwrap_or_default().to_string_lossy();
+                rom_file.with_file_name(format!("{}.fixed.rom", basename))
+            });
+            let fixed = fix_checksums(file, &out_file, &firmware_bundle_info)
+                .expect("Cannot fix checksums");
+            match &args.output {
+                Output::Debug | Output::Report => {
+                    for fix in &fixed.fixes {
+                        println!(
+                            "FIXED {} checksum at {:#010X}: {:#04X} -> {:#04X}",
+                            fix.structure, fix.offset, fix.old_value, fix.new_value
+                        );
+                    }
+                    println!("Written {}", fixed.path.display());
+                    print_check_results(&fixed.results);
+                }
+                Output::Json => {
+                    print_json(&fixed, args.json_compact, "fixed ROM");
+                }
+            }
+            if !fixed.results.iter().all(|result| result.passed()) {
+                std::process::exit(1);
+            }
+        }
+        Command::Validate => {
+            let results = firmware_bundle_info
+                .validate(file)
+                .expect("Cannot validate firmware");
+            match &args.output {
+                Output::Debug | Output::Report => {
+                    print_check_results(&results);
+                }
+                Output::Json => {
+                    print_json(&results, args.json_compact, "validation results");
+                }
+            }
+            if !results.iter().all(|result| result.passed()) {
+                std::process::exit(1);
+            }
+        }
     }
 }
@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: MIT
 
-use crate::pci_legacy::{PciExpansionRomDataHeader, PciExpansionRomIndicator};
-use crate::{FirmwareRegion, VersionHex4, FIRMWARE_REGION_ALIGN};
+use crate::pci_legacy::{
+    PciExpansionRomCodeType, PciExpansionRomDataHeader, PciExpansionRomIndicator,
+};
+use crate::{FirmwareRegion, VersionHex4};
 use binread::BinRead;
 use bitflags::bitflags;
 use derivative::Derivative;
@@ -16,7 +18,7 @@ pub const NV_ROM_SIGNATURE: &[u8] = b"VN";
 pub const NVGI_SIGNATURE: &[u8] = b"NVGI";
 pub const RFRD_SIGNATURE: &[u8] = b"RFRD";
 
-const NV_PCI_DATA_STRUCTURE_SIGNATURE: &[u8] = b"NPDS";
+pub(crate) const NV_PCI_DATA_STRUCTURE_SIGNATURE: &[u8] = b"NPDS";
 const NV_PCI_DATA_EXTENDED_STRUCTURE_SIGNATURE: &[u8] = b"NPDE";
 
 #[derive(BinRead, Debug, Clone, Serialize)]
@@ -29,9 +31,21 @@ pub struct NvgiHeader {
     pub size: u32,
 }
 
+impl NvgiHeader {
+    /// Tentative: the first unknown field looks like a header version, it stays the same across
+    /// all NVGI regions of a firmware
+    pub fn version(&self) -> u16 {
+        self.unknown1
+    }
+
+    /// Tentative: the second unknown field looks like a flags field
+    pub fn flags(&self) -> u16 {
+        self.unknown2
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct NvgiRegion {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: NvgiHeader,
@@ -42,6 +56,12 @@ pub struct NvgiRegion {
     pub data_offset_in_firmware: u64,
 }
 
+impl NvgiRegion {
+    pub fn version(&self) -> u16 {
+        self.header.version()
+    }
+}
+
 impl FirmwareRegion for NvgiRegion {
     fn offset_in_firmware(&self) -> u64 {
         self.offset_in_firmware
@@ -50,6 +70,10 @@ impl FirmwareRegion for NvgiRegion {
     fn region_size(&self) -> u64 {
         self.data_size
     }
+
+    fn kind(&self) -> &'static str {
+        "nvgi"
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
@@ -64,7 +88,6 @@ pub struct RfrdHeader {
 
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct RfrdRegion {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: RfrdHeader,
@@ -78,6 +101,10 @@ impl FirmwareRegion for RfrdRegion {
     fn region_size(&self) -> u64 {
         16
     }
+
+    fn kind(&self) -> &'static str {
+        "rfrd"
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
@@ -105,24 +132,34 @@ bitflags! {
 
 #[derive(BinRead, Derivative, Clone, Serialize)]
 #[derivative(Debug)]
+#[br(import(max_image_bytes: u64))]
 pub struct NvidiaPciExpansionRom {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: NvidiaPciExpansionRomHeader,
+    #[br(parse_with = crate::check_signature, args(offset_in_firmware, header.pcir_offset, NV_PCI_DATA_STRUCTURE_SIGNATURE))]
+    #[derivative(Debug = "ignore")]
+    #[serde(skip)]
+    _pcir_signature: (),
     #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware + header.pcir_offset as u64))]
     #[br(assert(data_header.signature == NV_PCI_DATA_STRUCTURE_SIGNATURE))]
     pub data_header: PciExpansionRomDataHeader,
     #[br(align_before = 16)]
     #[br(try)]
     pub data_header_extended: Option<NvidiaPciDataExtended>,
-    #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware))]
-    #[br(count(data_header.image_length))]
+    #[br(parse_with = crate::read_image_data, args(offset_in_firmware, data_header.image_length, max_image_bytes))]
     #[derivative(Debug = "ignore")]
     #[serde(skip)]
     pub data: Vec<u8>,
 }
 
+impl NvidiaPciExpansionRom {
+    /// HDCP key blocks are stored as an image of their own, its content is not decoded
+    pub fn is_hdcp(&self) -> bool {
+        self.data_header.code_type == PciExpansionRomCodeType::NvidiaHDCP
+    }
+}
+
 impl FirmwareRegion for NvidiaPciExpansionRom {
     fn offset_in_firmware(&self) -> u64 {
         self.offset_in_firmware
@@ -131,6 +168,14 @@ impl FirmwareRegion for NvidiaPciExpansionRom {
     fn region_size(&self) -> u64 {
         self.data_header.image_length as u64 * 512
     }
+
+    fn kind(&self) -> &'static str {
+        if self.is_hdcp() {
+            "hdcp"
+        } else {
+            "nvidia-pci"
+        }
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
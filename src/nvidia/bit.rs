@@ -2,20 +2,59 @@
 
 use crate::Result;
 use crate::{Error, VersionHex4};
-use binread::{BinRead, BinReaderExt};
+use binread::{BinRead, BinReaderExt, BinResult, ReadOptions};
 use bitflags::bitflags;
 use serde::Serialize;
+use std::cmp::Ordering;
 use std::ffi::CStr;
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
 
+pub mod dfp;
+pub mod mxm;
+pub mod nvinit;
 pub mod nvlink;
 pub mod perf;
 
 pub const BIT_SIGNATURE: &[u8] = b"BIT\0";
+pub const BIOS_TOKEN_ID: u8 = 0x42;
 
 //const BIT_HEADER_IDENTIFIER: u16 = 0xB8FF;
 
+/// Known BIT token ids and their names, including the tokens that are not parsed yet.
+pub const BIT_TOKEN_NAMES: &[(u8, &str)] = &[
+    (0x32, "I2C Pointers"),
+    (0x41, "DAC Pointers"),
+    (0x42, "BIOS Data"),
+    (0x43, "Clock Pointers"),
+    (0x44, "DFP Pointers"),
+    (0x49, "NVINIT Pointers"),
+    (0x4C, "LVDS Pointers"),
+    (0x4D, "Memory Pointers"),
+    (0x4E, "No Operation"),
+    (0x50, "Performance Pointers"),
+    (0x52, "Bridge Firmware Data"),
+    (0x53, "String Pointers"),
+    (0x54, "TMDS Pointers"),
+    (0x55, "Display Pointers"),
+    (0x56, "Virtual Pointers"),
+    (0x63, "32-bit Pointers"),
+    (0x64, "DP Pointers"),
+    (0x69, "Information"),
+    (0x6E, "DCB Pointers"),
+    (0x70, "Falcon Data"),
+    (0x75, "UEFI Data"),
+    (0x78, "MXM Configuration Data"),
+];
+
+pub fn token_name(id: u8) -> &'static str {
+    BIT_TOKEN_NAMES
+        .iter()
+        .find(|(token_id, _)| *token_id == id)
+        .map(|(_, name)| *name)
+        .unwrap_or("Unknown")
+}
+
 fn try_map_to_string(bytes: Vec<u8>) -> Option<String> {
     CStr::from_bytes_until_nul(bytes.as_slice())
         .ok()
@@ -24,14 +63,30 @@ fn try_map_to_string(bytes: Vec<u8>) -> Option<String> {
         .map(|str| str.to_string())
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct BITStructure {
+    #[br(parse_with = crate::stream_position)]
+    pub offset_in_region: u64,
+    /// Firmware offset of the region the structure was found in
+    #[br(default)]
+    pub region_offset_in_firmware: Option<u64>,
     pub header: BITHeader,
     #[br(count = header.token_entries)]
     pub tokens: Vec<BITToken>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+impl BITStructure {
+    pub fn tokens_with_data<'a, S: Seek + Read>(
+        &'a self,
+        reader: &'a mut S,
+    ) -> impl Iterator<Item = Result<(u8, BITTokenType)>> + 'a {
+        self.tokens
+            .iter()
+            .map(move |token| token.data(&mut *reader).map(|data| (token.id, data)))
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct BITHeader {
     pub id: u16,
     #[br(assert(signature == BIT_SIGNATURE))]
@@ -44,16 +99,25 @@ pub struct BITHeader {
     pub header_checksum: u8,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(little)]
 pub struct BITToken {
     pub id: u8,
+    #[br(calc = token_name(id))]
+    pub name: &'static str,
     pub data_version: u8,
     pub data_size: u16,
     pub data_pointer: u16,
 }
 
 impl BITToken {
+    // The token is a plain array of pointers, its length is given only by the data size
+    fn read_ptrs_32_bit<S: Seek + Read>(&self, source: &mut S) -> Result<Vec<u32>> {
+        (0..self.data_size / 4)
+            .map(|_| Ok(source.read_le::<u32>()?))
+            .collect()
+    }
+
     pub fn data<S: Seek + Read>(&self, source: &mut S) -> Result<BITTokenType> {
         if self.data_pointer == 0 {
             return Ok(BITTokenType::Nop);
@@ -62,7 +126,7 @@ impl BITToken {
             match self.id {
                 0x32 => Ok(BITTokenType::I2C(source.read_le()?)),
                 0x41 => Ok(BITTokenType::Dac(source.read_le()?)),
-                0x42 => Ok(BITTokenType::Bios(source.read_le()?)),
+                BIOS_TOKEN_ID => Ok(BITTokenType::Bios(source.read_le()?)),
                 0x43 => Ok(BITTokenType::Clock(source.read_le()?)),
                 0x44 => Ok(BITTokenType::Dfp(source.read_le()?)),
                 0x49 => Ok(BITTokenType::NvInit(source.read_le()?)),
@@ -75,22 +139,22 @@ impl BITToken {
                 0x54 => Ok(BITTokenType::Tmds(source.read_le()?)),
                 0x55 => Ok(BITTokenType::Display(source.read_le()?)),
                 0x56 => Ok(BITTokenType::Virtual(source.read_le()?)),
-                0x63 => Ok(BITTokenType::Ptrs32Bit(source.read_le()?)),
+                0x63 => Ok(BITTokenType::Ptrs32Bit(self.read_ptrs_32_bit(source)?)),
                 0x64 => Ok(BITTokenType::Dp(source.read_le()?)),
                 0x6E => Ok(BITTokenType::Dcb(source.read_le()?)),
                 0x70 => Ok(BITTokenType::Falcon(source.read_le()?)),
                 0x75 => Ok(BITTokenType::Uefi(source.read_le()?)),
                 0x78 => Ok(BITTokenType::Mxm(source.read_le()?)),
                 _ => Err(Error::InvalidFormat(format!(
-                    "Unexpected BIT token id: {}",
-                    self.id
+                    "Unexpected BIT token id: {:#04X} ({})",
+                    self.id, self.name
                 ))),
             }
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum BITTokenType {
     I2C(I2CPtrsToken),
     Dac(DACPtrsToken),
@@ -107,6 +171,8 @@ pub enum BITTokenType {
     Tmds(TmdsPtrsToken),
     Display(DisplayPtrsToken),
     Virtual(VirtualPtrsToken),
+    /// Image offsets of the structures that don't fit 16-bit pointers, `data_size / 4` entries.
+    /// The structure behind each index is not documented.
     Ptrs32Bit(Vec<u32>),
     Dp(DpPtrsToken),
     Dcb(DcbPtrsToken),
@@ -115,19 +181,47 @@ pub enum BITTokenType {
     Mxm(MxmDataToken),
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+impl BITTokenType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BITTokenType::I2C(_) => "I2C",
+            BITTokenType::Dac(_) => "DAC",
+            BITTokenType::Bios(_) => "BIOS",
+            BITTokenType::Clock(_) => "Clock",
+            BITTokenType::Dfp(_) => "DFP",
+            BITTokenType::NvInit(_) => "NVINIT",
+            BITTokenType::Lvds(_) => "LVDS",
+            BITTokenType::Memory(_) => "Memory",
+            BITTokenType::Nop => "NOP",
+            BITTokenType::Perf(_) => "Perf",
+            BITTokenType::BridgeFw(_) => "Bridge FW",
+            BITTokenType::String(_) => "String",
+            BITTokenType::Tmds(_) => "TMDS",
+            BITTokenType::Display(_) => "Display",
+            BITTokenType::Virtual(_) => "Virtual",
+            BITTokenType::Ptrs32Bit(_) => "32-bit pointers",
+            BITTokenType::Dp(_) => "DP",
+            BITTokenType::Dcb(_) => "DCB",
+            BITTokenType::Falcon(_) => "Falcon",
+            BITTokenType::Uefi(_) => "UEFI",
+            BITTokenType::Mxm(_) => "MXM",
+        }
+    }
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct I2CPtrsToken {
     pub i2c_scripts_ptr: u16,
     pub ext_hw_mon_init_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct DACPtrsToken {
     pub dac_data_ptr: u16,
     pub dac_flags: DacFlags,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct DacFlags(u8);
 bitflags! {
     impl DacFlags: u8 {
@@ -135,7 +229,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct BiosDataToken {
     pub bios_version: VersionHex4,
     pub bios_oem_version: u8,
@@ -157,7 +251,74 @@ pub struct BiosDataToken {
     pub compression_data_table: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+const DATA_RANGE_SIZE: usize = 8;
+const MAX_DATA_RANGE_COUNT: usize = 64;
+
+/// Image ranges referenced by the BIOS data token, the list ends at an all-zero range
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DataRangeTable {
+    #[br(parse_with = read_data_ranges)]
+    pub ranges: Vec<DataRange>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DataRange {
+    pub start: u32,
+    #[br(assert(end >= start, Error::InvalidFormat(format!("Unexpected data range: {:#X}..{:#X}", start, end))))]
+    pub end: u32,
+}
+
+fn read_data_ranges<R: Read + Seek>(
+    reader: &mut R,
+    ro: &ReadOptions,
+    _: (),
+) -> BinResult<Vec<DataRange>> {
+    let mut ranges = Vec::new();
+    let mut bytes = [0u8; DATA_RANGE_SIZE];
+    while ranges.len() < MAX_DATA_RANGE_COUNT {
+        let range_offset = reader.stream_position()?;
+        if crate::read_available(reader, &mut bytes) < bytes.len() || bytes.iter().all(|&b| b == 0)
+        {
+            break;
+        }
+        reader.seek(SeekFrom::Start(range_offset))?;
+        ranges.push(crate::traced(reader, ro, ("data_range_table.ranges", ()))?);
+    }
+    Ok(ranges)
+}
+
+impl BiosDataToken {
+    /// vBIOS version with the OEM version, as shown by the vendor tools
+    pub fn version(&self) -> String {
+        format!("{}.{:02X}", self.bios_version, self.bios_oem_version)
+    }
+
+    pub fn post_capabilities(&self) -> PostCapabilities {
+        PostCapabilities {
+            post_callbacks: self
+                .int15_post_callbacks
+                .iter_names()
+                .map(|(name, _)| name)
+                .collect(),
+            system_callbacks: self
+                .int15_system_callbacks
+                .iter_names()
+                .map(|(name, _)| name)
+                .collect(),
+            frame_count: self.frame_count,
+        }
+    }
+}
+
+/// INT15 callbacks the legacy BIOS makes into the system BIOS, by flag name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PostCapabilities {
+    pub post_callbacks: Vec<&'static str>,
+    pub system_callbacks: Vec<&'static str>,
+    pub frame_count: u16,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Int15PostCallbacks(u16);
 bitflags! {
     impl Int15PostCallbacks: u16 {
@@ -172,7 +333,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Int15SystemCallbacks(u16);
 bitflags! {
     impl Int15SystemCallbacks: u16 {
@@ -186,7 +347,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct ModuleMapExternal0(u8);
 bitflags! {
     impl ModuleMapExternal0: u8 {
@@ -195,7 +356,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct ClockPtrsToken {
     pub pll_info_table_ptr: u32,
     pub vbe_mode_pclk_table_ptr: u32,
@@ -206,13 +367,13 @@ pub struct ClockPtrsToken {
     pub frequency_controller_table_ptr: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct DfpPtrsToken {
     pub fp_established_ptr: u16,
     pub fp_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct NvinitPtrsToken {
     pub init_script_table_ptr: u16,
     pub macro_index_table_ptr: u16,
@@ -233,12 +394,12 @@ pub struct NvinitPtrsToken {
     pub boot_scripts_size_non_gc6: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct LvdsPtrsToken {
     pub lvds_info_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct MemoryPtrsToken {
     pub memory_strap_data_count: u8,
     pub memory_strap_translation_table_ptr: u16,
@@ -248,7 +409,7 @@ pub struct MemoryPtrsToken {
     pub memory_script_list_ptr: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct PerfPtrsToken {
     pub performance_table_ptr: u32,
     pub memory_clock_table_ptr: u32,
@@ -292,7 +453,7 @@ pub struct PerfPtrsToken {
     pub low_power_nvlink_table_ptr: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Copy, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct StringPtrsToken {
     pub sign_on_message_ptr: u16,
     pub sign_on_message_maximum_length: u8,
@@ -310,7 +471,7 @@ pub struct StringPtrsToken {
     pub oem_product_revision_size: u8,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: StringPtrsToken))]
 pub struct StringToken {
     #[br(seek_before = SeekFrom::Start(ptrs.sign_on_message_ptr as u64))]
@@ -350,19 +511,88 @@ pub struct StringToken {
     pub oem_product_revision: Option<String>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+/// PCI vendor ids of board vendors by the lowercase start of a word of their OEM vendor name
+const OEM_VENDOR_IDS: &[(&str, u16)] = &[
+    ("nvidia", 0x10DE),
+    ("asus", 0x1043),
+    ("micro-star", 0x1462),
+    ("msi", 0x1462),
+    ("gigabyte", 0x1458),
+    ("evga", 0x3842),
+    ("zotac", 0x19DA),
+    ("palit", 0x1569),
+    ("gainward", 0x10B0),
+    ("pny", 0x196E),
+    ("galax", 0x1B4C),
+    ("kfa2", 0x1B4C),
+    ("colorful", 0x7377),
+    ("dell", 0x1028),
+    ("hewlett-packard", 0x103C),
+    ("lenovo", 0x17AA),
+];
+
+/// Position of the first word of `name` that starts with `prefix`
+fn word_prefix_position(name: &str, prefix: &str) -> Option<usize> {
+    name.match_indices(prefix)
+        .map(|(position, _)| position)
+        .find(|&position| {
+            !name[..position]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+        })
+}
+
+/// OEM strings of the string token, with the PCI vendor id matched from the vendor name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OemIdentity {
+    pub vendor_name: Option<String>,
+    pub product_name: Option<String>,
+    pub product_revision: Option<String>,
+    pub vendor_id: Option<u16>,
+}
+
+impl StringToken {
+    pub fn oem_identity(&self) -> Option<OemIdentity> {
+        if self.oem_vendor_name.is_none()
+            && self.oem_product_name.is_none()
+            && self.oem_product_revision.is_none()
+        {
+            return None;
+        }
+        // The vendor named first wins, e.g. a board vendor over a "for NVIDIA" suffix
+        let vendor_id = self.oem_vendor_name.as_ref().and_then(|name| {
+            let name = name.to_lowercase();
+            OEM_VENDOR_IDS
+                .iter()
+                .filter_map(|(prefix, vendor_id)| {
+                    word_prefix_position(&name, prefix).map(|position| (position, *vendor_id))
+                })
+                .min_by_key(|(position, _)| *position)
+                .map(|(_, vendor_id)| vendor_id)
+        });
+        Some(OemIdentity {
+            vendor_name: self.oem_vendor_name.clone(),
+            product_name: self.oem_product_name.clone(),
+            product_revision: self.oem_product_revision.clone(),
+            vendor_id,
+        })
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct TmdsPtrsToken {
     pub tmds_info_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DisplayPtrsToken {
     pub display_scripting_table_ptr: u16,
     pub display_control_flags: DisplayControlFlags,
     pub sli_table_header_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DisplayControlFlags(u8);
 bitflags! {
     impl DisplayControlFlags: u8 {
@@ -376,36 +606,57 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VirtualPtrsToken {
     pub virtual_strap_field_table_ptr: u16,
     pub virtual_strap_field_register: u16,
     pub translation_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DpPtrsToken {
     pub dp_info_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DcbPtrsToken {
     pub dcb_header_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct FalconDataToken {
     pub falcon_ucode_table_ptr: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct UefiDataToken {
     pub minimum_uefi_driver_version: u32,
     pub uefi_compatibility_level: u8,
     pub uefi_flags: UefiFlags,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+impl UefiDataToken {
+    pub fn min_driver_version(&self) -> VersionHex4 {
+        VersionHex4::from_u32_le(self.minimum_uefi_driver_version)
+    }
+
+    /// Formatted like the GOP version, most significant byte first
+    pub fn min_driver_version_str(&self) -> String {
+        self.min_driver_version().to_string()
+    }
+
+    /// Orders the required minimum against the given driver version
+    pub fn cmp_min_driver_version(&self, version: &VersionHex4) -> Ordering {
+        self.minimum_uefi_driver_version.cmp(&version.to_u32_le())
+    }
+
+    /// Whether a driver of the given version meets the minimum requirement
+    pub fn is_driver_version_supported(&self, version: &VersionHex4) -> bool {
+        self.cmp_min_driver_version(version) != Ordering::Greater
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct UefiFlags(u64);
 bitflags! {
     impl UefiFlags: u64 {
@@ -415,7 +666,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MxmDataToken {
     pub module_spec_version: u8,
     pub module_flags: ModuleFlags,
@@ -425,7 +676,17 @@ pub struct MxmDataToken {
     pub mxm_aux_to_ccb_table_ptr: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+impl MxmDataToken {
+    /// The module is MXM and its structure passed the validation
+    pub fn is_valid_mxm(&self) -> bool {
+        self.module_flags.bits() & 0x0F != ModuleFlags::NotMxm.bits()
+            && !self
+                .config_flags
+                .contains(ConfigFlags::MxmStructureValidationFailed)
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ModuleFlags(u8);
 bitflags! {
     impl ModuleFlags: u8 {
@@ -438,7 +699,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ConfigFlags(u8);
 bitflags! {
     impl ConfigFlags: u8 {
@@ -455,7 +716,7 @@ bitflags! {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct BridgeFwDataToken {
     pub firmware_version: u32,
     pub firmware_oem_version: u8,
@@ -465,9 +726,15 @@ pub struct BridgeFwDataToken {
     pub engineering_product_name_ptr: u16,
     pub engineering_product_name_size: u8,
     pub instance_id: u16,
+    #[br(restore_position)]
+    #[br(seek_before = SeekFrom::Start(engineering_product_name_ptr as u64))]
+    #[br(count = engineering_product_name_size)]
+    #[br(map = try_map_to_string)]
+    #[br(if(engineering_product_name_ptr > 0))]
+    pub engineering_product_name: Option<String>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: ClockPtrsToken))]
 pub struct PllInfo {
     #[br(seek_before = SeekFrom::Start(ptrs.pll_info_table_ptr as u64))]
@@ -476,7 +743,7 @@ pub struct PllInfo {
     pub entries: Vec<PllInfoEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PllInfoHeader {
     pub version: u8,
     pub header_size: u8,
@@ -485,7 +752,7 @@ pub struct PllInfoHeader {
     pub entry_count: u8,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PllInfoEntry {
     pub id: u8,
     pub ref_min_mhz: u16,
@@ -501,3 +768,193 @@ pub struct PllInfoEntry {
     pub pl_min: u8,
     pub pl_max: u8,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::nvidia::bit::{
+        token_name, BITToken, BITTokenType, BiosDataToken, BridgeFwDataToken, DataRange,
+        DataRangeTable, StringToken, UefiDataToken,
+    };
+    use crate::VersionHex4;
+    use binread::{BinRead, BinReaderExt};
+    use std::cmp::Ordering;
+    use std::io::Cursor;
+
+    /// Pointers token with all the pointers zeroed, the tests set the pointers they need
+    pub(crate) fn zeroed_token<T: BinRead<Args = ()>>() -> T {
+        Cursor::new(vec![0u8; 256]).read_le().unwrap()
+    }
+
+    fn bridge_fw_token_bytes(name_ptr: u16, name_size: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0x0102_0304u32.to_le_bytes());
+        bytes.push(0x05);
+        bytes.extend_from_slice(&0x0200u16.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&name_ptr.to_le_bytes());
+        bytes.push(name_size);
+        bytes.extend_from_slice(&0x0001u16.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_oem_identity() {
+        let mut token = StringToken {
+            sign_on_message: None,
+            version_string: None,
+            copyright_string: None,
+            oem_string: None,
+            oem_vendor_name: None,
+            oem_product_name: None,
+            oem_product_revision: None,
+        };
+        assert!(token.oem_identity().is_none());
+
+        token.oem_vendor_name = Some("Micro-Star International".to_string());
+        token.oem_product_name = Some("RTX 3060 Ti".to_string());
+        let identity = token.oem_identity().unwrap();
+        assert_eq!(Some(0x1462), identity.vendor_id);
+        assert_eq!(Some("RTX 3060 Ti".to_string()), identity.product_name);
+        assert!(identity.product_revision.is_none());
+
+        token.oem_vendor_name = Some("Unknown Vendor".to_string());
+        assert!(token.oem_identity().unwrap().vendor_id.is_none());
+
+        token.oem_vendor_name = Some("Transmission Modell GmbH".to_string());
+        assert!(token.oem_identity().unwrap().vendor_id.is_none());
+
+        token.oem_vendor_name = Some("ASUSTeK Computer Inc. for NVIDIA".to_string());
+        assert_eq!(Some(0x1043), token.oem_identity().unwrap().vendor_id);
+
+        token.oem_vendor_name = Some("NVIDIA Corporation".to_string());
+        assert_eq!(Some(0x10DE), token.oem_identity().unwrap().vendor_id);
+    }
+
+    #[test]
+    fn test_post_capabilities() {
+        let mut bytes = vec![0u8; 33];
+        bytes[6..8].copy_from_slice(&0b1_0001u16.to_le_bytes());
+        bytes[8..10].copy_from_slice(&0b100u16.to_le_bytes());
+        bytes[10..12].copy_from_slice(&60u16.to_le_bytes());
+        let token: BiosDataToken = Cursor::new(bytes).read_le().unwrap();
+
+        let capabilities = token.post_capabilities();
+        assert_eq!(
+            vec!["GetPanelId", "PerformPostCompleteCallback"],
+            capabilities.post_callbacks
+        );
+        assert_eq!(
+            vec!["MakeSpreadSpectrumBypassCallback"],
+            capabilities.system_callbacks
+        );
+        assert_eq!(60, capabilities.frame_count);
+    }
+
+    #[test]
+    fn test_ptrs_32_bit() {
+        let token = BITToken {
+            id: 0x63,
+            name: token_name(0x63),
+            data_version: 1,
+            data_size: 10,
+            data_pointer: 2,
+        };
+        let mut bytes = vec![0xFF, 0xFF];
+        bytes.extend_from_slice(&0x0001_2000u32.to_le_bytes());
+        bytes.extend_from_slice(&0x0003_4000u32.to_le_bytes());
+        bytes.extend_from_slice(&0xDEAD_BEEFu32.to_le_bytes());
+
+        assert_eq!(
+            BITTokenType::Ptrs32Bit(vec![0x0001_2000, 0x0003_4000]),
+            token.data(&mut Cursor::new(bytes)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_data_range_table() {
+        let mut bytes = Vec::new();
+        for value in [0x0000u32, 0x0200, 0x0400, 0x1000, 0, 0, 0xFFFF, 0xFFFF] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let table: DataRangeTable = Cursor::new(bytes).read_le().unwrap();
+        assert_eq!(
+            vec![
+                DataRange {
+                    start: 0,
+                    end: 0x200
+                },
+                DataRange {
+                    start: 0x400,
+                    end: 0x1000
+                },
+            ],
+            table.ranges
+        );
+
+        let table: DataRangeTable = Cursor::new([0x00, 0x10, 0xFF]).read_le().unwrap();
+        assert!(table.ranges.is_empty());
+
+        let mut bytes = Vec::new();
+        for value in [0x1000u32, 0x0400] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        assert!(Cursor::new(bytes).read_le::<DataRangeTable>().is_err());
+    }
+
+    #[test]
+    fn test_bridge_fw_engineering_product_name() {
+        let mut bytes = bridge_fw_token_bytes(24, 8);
+        bytes.extend_from_slice(b"NVLINK\0\0");
+        let token: BridgeFwDataToken = Cursor::new(bytes).read_le().unwrap();
+
+        assert_eq!(Some("NVLINK".to_string()), token.engineering_product_name);
+        assert_eq!(1, token.instance_id);
+    }
+
+    #[test]
+    fn test_bridge_fw_without_engineering_product_name() {
+        let bytes = bridge_fw_token_bytes(0, 0);
+        let token: BridgeFwDataToken = Cursor::new(bytes).read_le().unwrap();
+
+        assert_eq!(None, token.engineering_product_name);
+    }
+
+    #[test]
+    fn test_bridge_fw_equality() {
+        let bytes = bridge_fw_token_bytes(0, 0);
+        let token: BridgeFwDataToken = Cursor::new(&bytes).read_le().unwrap();
+        let same_token: BridgeFwDataToken = Cursor::new(&bytes).read_le().unwrap();
+        let mut other_bytes = bytes.clone();
+        other_bytes[4] = 0x06;
+        let other_token: BridgeFwDataToken = Cursor::new(other_bytes).read_le().unwrap();
+
+        assert_eq!(token, same_token);
+        assert_ne!(token, other_token);
+    }
+
+    #[test]
+    fn test_token_name() {
+        assert_eq!("Performance Pointers", token_name(0x50));
+        assert_eq!("Information", token_name(0x69));
+        assert_eq!("Unknown", token_name(0xFF));
+    }
+
+    #[test]
+    fn test_uefi_min_driver_version() {
+        let mut bytes = 0x0002_0030u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let token: UefiDataToken = Cursor::new(bytes).read_le().unwrap();
+
+        assert_eq!("00.02.00.30", token.min_driver_version_str());
+        let older = VersionHex4::from_u32_le(0x0002_0000);
+        let newer = VersionHex4::from_u32_le(0x0002_0100);
+        assert_eq!(Ordering::Greater, token.cmp_min_driver_version(&older));
+        assert_eq!(
+            Ordering::Equal,
+            token.cmp_min_driver_version(&token.min_driver_version())
+        );
+        assert!(!token.is_driver_version_supported(&older));
+        assert!(token.is_driver_version_supported(&newer));
+    }
+}
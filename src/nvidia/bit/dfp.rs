@@ -0,0 +1,345 @@
+// SPDX-License-Identifier: MIT
+
+use super::DfpPtrsToken;
+use crate::Error;
+use binread::BinRead;
+use bitflags::bitflags;
+use serde::Serialize;
+use std::io::SeekFrom;
+
+/// EDID-like established timings, byte 0 bit 7 is the most significant bit.
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[br(map = |bytes: [u8; 3]| Self(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]])))]
+pub struct EstablishedTimings(u32);
+bitflags! {
+    impl EstablishedTimings: u32 {
+        const Mode720x400At70 = 1 << 23;
+        const Mode720x400At88 = 1 << 22;
+        const Mode640x480At60 = 1 << 21;
+        const Mode640x480At67 = 1 << 20;
+        const Mode640x480At72 = 1 << 19;
+        const Mode640x480At75 = 1 << 18;
+        const Mode800x600At56 = 1 << 17;
+        const Mode800x600At60 = 1 << 16;
+        const Mode800x600At72 = 1 << 15;
+        const Mode800x600At75 = 1 << 14;
+        const Mode832x624At75 = 1 << 13;
+        const Mode1024x768At87Interlaced = 1 << 12;
+        const Mode1024x768At60 = 1 << 11;
+        const Mode1024x768At70 = 1 << 10;
+        const Mode1024x768At75 = 1 << 9;
+        const Mode1280x1024At75 = 1 << 8;
+        const Mode1152x870At75 = 1 << 7;
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: DfpPtrsToken))]
+pub struct FlatPanelTable {
+    #[br(seek_before = SeekFrom::Start(ptrs.fp_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("flat_panel_table.header", ()))]
+    pub header: FlatPanelTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptrs.fp_table_ptr as u64 + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    #[br(parse_with = crate::traced, args("flat_panel_table.entries", (header.entry_size,)))]
+    pub entries: Vec<FlatPanelTableEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlatPanelTableHeader {
+    #[br(assert(version == 0x30 || version == 0x40, Error::InvalidFormat(format!("Unsupported flat panel table version: {:#X}", version))))]
+    pub version: u8,
+    pub header_size: u8,
+    #[serde(skip)]
+    size_and_count: [u8; 2],
+    // Entry size and count are swapped in version 4.0
+    #[br(calc = if version == 0x30 { size_and_count[0] } else { size_and_count[1] })]
+    #[br(assert(entry_size >= 38, Error::InvalidFormat(format!("Unexpected flat panel table entry size: {}", entry_size))))]
+    pub entry_size: u8,
+    #[br(calc = if version == 0x30 { size_and_count[1] } else { size_and_count[0] })]
+    pub entry_count: u8,
+}
+
+/// Panel mode, timing values are stored minus one.
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(entry_size: u8))]
+pub struct FlatPanelTableEntry {
+    pub index: u8,
+    pub panel_type: u16,
+    /// Red, green, blue and reserved
+    pub bits_per_color: [u8; 4],
+    /// Pixel clock in 10 kHz units
+    pub pixel_clock: u16,
+    pub h_active: u16,
+    pub h_display: u16,
+    pub h_valid_start: u16,
+    pub h_valid_end: u16,
+    pub h_sync_start: u16,
+    pub h_sync_end: u16,
+    pub h_total: u16,
+    pub v_active: u16,
+    pub v_display: u16,
+    pub v_valid_start: u16,
+    pub v_valid_end: u16,
+    pub v_sync_start: u16,
+    pub v_sync_end: u16,
+    pub v_total: u16,
+    pub flags: FlatPanelModeFlags,
+    #[br(parse_with = crate::read_remaining, args("Flat panel table entry", entry_size, 38))]
+    pub unknown: Vec<u8>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FlatPanelModeFlags(u8);
+bitflags! {
+    impl FlatPanelModeFlags: u8 {
+        const PositiveVSync = 0b00000001;
+        const PositiveHSync = 0b00010000;
+    }
+}
+
+impl FlatPanelTableEntry {
+    pub fn native_resolution(&self) -> (u32, u32) {
+        (self.h_display as u32 + 1, self.v_display as u32 + 1)
+    }
+
+    pub fn pixel_clock_khz(&self) -> u32 {
+        self.pixel_clock as u32 * 10
+    }
+
+    pub fn refresh_rate_hz(&self) -> Option<f64> {
+        let total_pixels = (self.h_total as u64 + 1) * (self.v_total as u64 + 1);
+        if self.pixel_clock == 0 {
+            None
+        } else {
+            Some(self.pixel_clock_khz() as f64 * 1000.0 / total_pixels as f64)
+        }
+    }
+
+    /// Panels with less than 8 bits per color are driven with dithering
+    pub fn dithering(&self) -> bool {
+        self.bits_per_color[..3]
+            .iter()
+            .any(|&bits| bits > 0 && bits < 8)
+    }
+}
+
+pub const EDID_HEADER: &[u8] = b"\x00\xFF\xFF\xFF\xFF\xFF\xFF\x00";
+const EDID_BLOCK_LEN: usize = 128;
+/// Offset of the established timings in an EDID base block
+pub const EDID_ESTABLISHED_TIMINGS_OFFSET: usize = 0x23;
+
+/// EDID base block with at most one extension block, as embedded in the image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edid {
+    pub offset_in_image: usize,
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EdidTiming {
+    pub pixel_clock_khz: u32,
+    pub h_active: u16,
+    pub v_active: u16,
+}
+
+impl Edid {
+    /// The base block followed by the extension block, if any
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Three-letter PNP id of the manufacturer
+    pub fn manufacturer(&self) -> String {
+        let id = u16::from_be_bytes([self.bytes[8], self.bytes[9]]);
+        [10, 5, 0]
+            .iter()
+            .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1F) as u8) as char)
+            .collect()
+    }
+
+    pub fn product_code(&self) -> u16 {
+        u16::from_le_bytes([self.bytes[10], self.bytes[11]])
+    }
+
+    /// The first detailed timing descriptor, `None` if it holds a display descriptor instead
+    pub fn preferred_timing(&self) -> Option<EdidTiming> {
+        let descriptor = &self.bytes[54..72];
+        let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]);
+        (pixel_clock > 0).then(|| EdidTiming {
+            pixel_clock_khz: pixel_clock as u32 * 10,
+            h_active: descriptor[2] as u16 | ((descriptor[4] as u16 >> 4) << 8),
+            v_active: descriptor[5] as u16 | ((descriptor[7] as u16 >> 4) << 8),
+        })
+    }
+}
+
+fn is_valid_edid_block(block: &[u8]) -> bool {
+    block.len() == EDID_BLOCK_LEN && block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) == 0
+}
+
+/// The EDID block at the offset of the image data, if it has a valid header and checksum
+pub fn edid_at(data: &[u8], offset: usize) -> Option<Edid> {
+    let base = data.get(offset..offset + EDID_BLOCK_LEN)?;
+    if !base.starts_with(EDID_HEADER) || !is_valid_edid_block(base) {
+        return None;
+    }
+    let mut len = EDID_BLOCK_LEN;
+    let extension = data.get(offset + EDID_BLOCK_LEN..offset + 2 * EDID_BLOCK_LEN);
+    if base[126] == 1 && extension.is_some_and(is_valid_edid_block) {
+        len += EDID_BLOCK_LEN;
+    }
+    Some(Edid {
+        offset_in_image: offset,
+        bytes: data[offset..offset + len].to_vec(),
+    })
+}
+
+/// The EDID block that holds the flat panel established timings, which are the established
+/// timings field of the panel EDID when the image embeds one
+pub fn flat_panel_edid(data: &[u8], ptrs: &DfpPtrsToken) -> Option<Edid> {
+    (ptrs.fp_established_ptr as usize)
+        .checked_sub(EDID_ESTABLISHED_TIMINGS_OFFSET)
+        .and_then(|offset| edid_at(data, offset))
+}
+
+/// Finds the EDID blocks with a valid header and checksum in the image data
+pub fn find_edids(data: &[u8]) -> Vec<Edid> {
+    let mut edids = Vec::new();
+    let mut offset = 0;
+    while offset + EDID_BLOCK_LEN <= data.len() {
+        match edid_at(data, offset) {
+            Some(edid) => {
+                offset += edid.bytes.len();
+                edids.push(edid);
+            }
+            None => offset += 1,
+        }
+    }
+    edids
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::bit::dfp::{
+        find_edids, flat_panel_edid, EdidTiming, EstablishedTimings, FlatPanelTable,
+        EDID_ESTABLISHED_TIMINGS_OFFSET, EDID_HEADER,
+    };
+    use crate::nvidia::bit::DfpPtrsToken;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    fn entry_bytes() -> Vec<u8> {
+        let mut bytes = vec![0x00];
+        bytes.extend_from_slice(&0x0001u16.to_le_bytes());
+        bytes.extend_from_slice(&[6, 6, 6, 0]);
+        bytes.extend_from_slice(&14850u16.to_le_bytes());
+        for value in [1920u16, 1919, 0, 1919, 2007, 2051, 2199] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [1080u16, 1079, 0, 1079, 1083, 1088, 1124] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.push(0x11);
+        bytes
+    }
+
+    #[test]
+    fn test_flat_panel_table_v4() {
+        let mut bytes = vec![0x40, 5, 1, 40, 0];
+        bytes.extend(entry_bytes());
+        bytes.extend_from_slice(&[0xAA, 0xBB]);
+        let ptrs = DfpPtrsToken {
+            fp_established_ptr: 0,
+            fp_table_ptr: 0,
+        };
+        let table: FlatPanelTable = Cursor::new(bytes).read_le_args((ptrs,)).unwrap();
+
+        assert_eq!(1, table.entries.len());
+        let entry = &table.entries[0];
+        assert_eq!((1920, 1080), entry.native_resolution());
+        assert_eq!(148500, entry.pixel_clock_khz());
+        assert_eq!(60, entry.refresh_rate_hz().unwrap().round() as u32);
+        assert!(entry.dithering());
+        assert_eq!(vec![0xAA, 0xBB], entry.unknown);
+    }
+
+    #[test]
+    fn test_established_timings() {
+        let timings: EstablishedTimings = Cursor::new([0x21, 0x08, 0x80]).read_le().unwrap();
+
+        assert_eq!(
+            EstablishedTimings::Mode640x480At60
+                | EstablishedTimings::Mode800x600At60
+                | EstablishedTimings::Mode1024x768At60
+                | EstablishedTimings::Mode1152x870At75,
+            timings
+        );
+    }
+
+    fn edid_block(extensions: u8) -> Vec<u8> {
+        let mut block = EDID_HEADER.to_vec();
+        // "DEL", product 0xA0C1
+        block.extend_from_slice(&[0x10, 0xAC, 0xC1, 0xA0]);
+        block.resize(54, 0);
+        // 148.5 MHz, 1920x1080
+        block.extend_from_slice(&[0x02, 0x3A, 0x80, 0x18, 0x71, 0x38, 0x2D, 0x40]);
+        block.resize(126, 0);
+        block.push(extensions);
+        let sum = block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        block.push(0u8.wrapping_sub(sum));
+        block
+    }
+
+    #[test]
+    fn test_find_edids() {
+        let mut data = vec![0xFFu8; 3];
+        data.extend(edid_block(0));
+        let mut corrupted = edid_block(0);
+        corrupted[127] ^= 1;
+        data.extend(corrupted);
+        data.extend(edid_block(1));
+        let mut extension = vec![0x02; 127];
+        let sum = extension.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        extension.push(0u8.wrapping_sub(sum));
+        data.extend(extension);
+
+        let edids = find_edids(&data);
+        assert_eq!(2, edids.len());
+        assert_eq!(3, edids[0].offset_in_image);
+        assert_eq!(128, edids[0].bytes().len());
+        assert_eq!(259, edids[1].offset_in_image);
+        assert_eq!(256, edids[1].bytes().len());
+        assert_eq!("DEL", edids[0].manufacturer());
+        assert_eq!(0xA0C1, edids[0].product_code());
+        assert_eq!(
+            Some(EdidTiming {
+                pixel_clock_khz: 148_500,
+                h_active: 1920,
+                v_active: 1080,
+            }),
+            edids[0].preferred_timing()
+        );
+    }
+
+    #[test]
+    fn test_flat_panel_edid() {
+        let mut data = vec![0u8; 16];
+        data.extend(edid_block(0));
+        data.extend(edid_block(0));
+        let ptrs = DfpPtrsToken {
+            fp_established_ptr: (128 + 16 + EDID_ESTABLISHED_TIMINGS_OFFSET) as u16,
+            fp_table_ptr: 0,
+        };
+
+        let edid = flat_panel_edid(&data, &ptrs).unwrap();
+        assert_eq!(144, edid.offset_in_image);
+        assert_eq!(128, edid.bytes().len());
+
+        let ptrs = DfpPtrsToken {
+            fp_established_ptr: 0x10,
+            fp_table_ptr: 0,
+        };
+        assert_eq!(None, flat_panel_edid(&data, &ptrs));
+    }
+}
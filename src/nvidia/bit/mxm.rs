@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+
+use crate::Error;
+use binread::BinRead;
+use serde::Serialize;
+use std::io::SeekFrom;
+
+/// Header of the MXM digital connector and AUX to CCB tables, the entries are one byte long.
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MxmMapTableHeader {
+    #[br(assert(version == 0x10 || version == 0x11, Error::InvalidFormat(format!("Unsupported MXM map table version: {:#X}", version))))]
+    pub version: u8,
+    pub header_size: u8,
+    pub unknown: u8,
+    pub entry_count: u8,
+}
+
+/// Maps MXM-SIS digital connections to SORs
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptr: u16))]
+pub struct MxmDigitalConnectorTable {
+    #[br(seek_before = SeekFrom::Start(ptr as u64))]
+    pub header: MxmMapTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptr as u64 + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    pub entries: Vec<MxmDigitalConnectorEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MxmDigitalConnectorEntry(u8);
+
+impl MxmDigitalConnectorEntry {
+    pub fn sor_index(&self) -> u8 {
+        self.0 & 0x0F
+    }
+
+    /// Bit 0 for link A, bit 1 for link B
+    pub fn link_mask(&self) -> u8 {
+        (self.0 >> 4) & 0x03
+    }
+}
+
+/// Maps MXM-SIS DDC ports to the communications control block entries
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptr: u16))]
+pub struct MxmAuxToCcbTable {
+    #[br(seek_before = SeekFrom::Start(ptr as u64))]
+    pub header: MxmMapTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptr as u64 + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    pub entries: Vec<MxmAuxToCcbEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MxmAuxToCcbEntry(u8);
+
+impl MxmAuxToCcbEntry {
+    /// CCB index used for DDC, `None` if the port is not routed
+    pub fn ddc_ccb_index(&self) -> Option<u8> {
+        Some(self.0 & 0x0F).filter(|&index| index != 0x0F)
+    }
+
+    /// CCB index used for DisplayPort AUX, `None` if the port is not routed
+    pub fn aux_ccb_index(&self) -> Option<u8> {
+        Some(self.0 >> 4).filter(|&index| index != 0x0F)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::bit::mxm::{MxmAuxToCcbTable, MxmDigitalConnectorTable};
+    use crate::Error;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_mxm_tables() {
+        let bytes = vec![0xFF, 0x10, 4, 0, 2, 0x12, 0x30, 0x11, 4, 0, 2, 0x21, 0xF3];
+        let mut cursor = Cursor::new(bytes);
+        let connectors: MxmDigitalConnectorTable = cursor.read_le_args((1,)).unwrap();
+        let ports: MxmAuxToCcbTable = cursor.read_le_args((7,)).unwrap();
+
+        assert_eq!(2, connectors.entries.len());
+        assert_eq!(2, connectors.entries[0].sor_index());
+        assert_eq!(1, connectors.entries[0].link_mask());
+        assert_eq!(0, connectors.entries[1].sor_index());
+        assert_eq!(3, connectors.entries[1].link_mask());
+
+        assert_eq!(Some(1), ports.entries[0].ddc_ccb_index());
+        assert_eq!(Some(2), ports.entries[0].aux_ccb_index());
+        assert_eq!(Some(3), ports.entries[1].ddc_ccb_index());
+        assert_eq!(None, ports.entries[1].aux_ccb_index());
+    }
+
+    #[test]
+    fn test_mxm_table_wrong_version() {
+        let err = Cursor::new(vec![0x20, 4, 0, 0])
+            .read_le_args::<MxmDigitalConnectorTable>((0,))
+            .unwrap_err();
+
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: MIT
+
+use super::NvinitPtrsToken;
+use binread::{BinRead, BinResult, ReadOptions};
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Init script opcodes reference macros and conditions by a byte index
+const MAX_TABLE_ENTRY_COUNT: usize = 256;
+
+pub const MACRO_INDEX_ENTRY_SIZE: u64 = 2;
+pub const MACRO_ENTRY_SIZE: u64 = 8;
+pub const CONDITION_ENTRY_SIZE: u64 = 12;
+pub const INIT_FUNCTION_ENTRY_SIZE: u64 = 4;
+
+impl NvinitPtrsToken {
+    fn table_ptrs(&self) -> [u16; 14] {
+        [
+            self.init_script_table_ptr,
+            self.macro_index_table_ptr,
+            self.macro_table_ptr,
+            self.condition_table_ptr,
+            self.io_condition_table_ptr,
+            self.io_flag_condition_table_ptr,
+            self.init_function_table_ptr,
+            self.vbios_private_boot_script_ptr,
+            self.data_arrays_table_ptr,
+            self.pcie_settings_script_ptr,
+            self.devinit_tables_ptr,
+            self.boot_scripts_ptr,
+            self.nvlink_configuration_data_ptr,
+            self.boot_scripts_non_gc6_ptr,
+        ]
+    }
+
+    /// Tentative: the tables have neither a header nor a terminator, so the entry count is
+    /// bounded by the closest NVINIT table after `ptr` and by the byte wide index. `None` if no
+    /// table follows, see `read_table_entries`.
+    pub fn table_entry_count(&self, ptr: u16, entry_size: u64) -> Option<usize> {
+        self.table_ptrs()
+            .into_iter()
+            .filter(|&next| next > ptr)
+            .min()
+            .map(|next_ptr| {
+                (((next_ptr - ptr) as u64 / entry_size) as usize).min(MAX_TABLE_ENTRY_COUNT)
+            })
+    }
+}
+
+/// Reads the entries of a NVINIT table at `ptr`. The last table has no closest table after it,
+/// so its entries end at the first all-zero entry or at the end of the image instead.
+fn read_table_entries<R: Read + Seek, T: BinRead<Args = ()>>(
+    reader: &mut R,
+    ro: &ReadOptions,
+    (name, ptrs, ptr, entry_size): (&'static str, NvinitPtrsToken, u16, u64),
+) -> BinResult<Vec<T>> {
+    reader.seek(SeekFrom::Start(ptr as u64))?;
+    if let Some(count) = ptrs.table_entry_count(ptr, entry_size) {
+        return (0..count)
+            .map(|_| crate::traced(reader, ro, (name, ())))
+            .collect();
+    }
+    let mut entries = Vec::new();
+    let mut bytes = vec![0u8; entry_size as usize];
+    while entries.len() < MAX_TABLE_ENTRY_COUNT {
+        let entry_offset = reader.stream_position()?;
+        if crate::read_available(reader, &mut bytes) < bytes.len() || bytes.iter().all(|&b| b == 0)
+        {
+            break;
+        }
+        reader.seek(SeekFrom::Start(entry_offset))?;
+        entries.push(crate::traced(reader, ro, (name, ()))?);
+    }
+    Ok(entries)
+}
+
+/// Ranges of the macro table run by the init script macro opcode
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: NvinitPtrsToken))]
+pub struct MacroIndexTable {
+    #[br(parse_with = read_table_entries, args("macro_index_table.entries", ptrs, ptrs.macro_index_table_ptr, MACRO_INDEX_ENTRY_SIZE))]
+    pub entries: Vec<MacroIndexEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MacroIndexEntry {
+    pub macro_table_index: u8,
+    pub macro_count: u8,
+}
+
+/// Register writes of the init script macros
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: NvinitPtrsToken))]
+pub struct MacroTable {
+    #[br(parse_with = read_table_entries, args("macro_table.entries", ptrs, ptrs.macro_table_ptr, MACRO_ENTRY_SIZE))]
+    pub entries: Vec<MacroEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MacroEntry {
+    pub register: u32,
+    pub value: u32,
+}
+
+impl MacroTable {
+    /// Register writes of the macro referenced by a macro index table entry
+    pub fn writes(&self, index: &MacroIndexEntry) -> &[MacroEntry] {
+        let start = (index.macro_table_index as usize).min(self.entries.len());
+        let end = (start + index.macro_count as usize).min(self.entries.len());
+        &self.entries[start..end]
+    }
+}
+
+/// Register conditions of the init script condition opcodes
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: NvinitPtrsToken))]
+pub struct ConditionTable {
+    #[br(parse_with = read_table_entries, args("condition_table.entries", ptrs, ptrs.condition_table_ptr, CONDITION_ENTRY_SIZE))]
+    pub entries: Vec<ConditionEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ConditionEntry {
+    pub register: u32,
+    pub mask: u32,
+    pub value: u32,
+}
+
+impl ConditionEntry {
+    pub fn is_met(&self, register_value: u32) -> bool {
+        register_value & self.mask == self.value
+    }
+}
+
+/// Devinit functions by id with the offsets of their init scripts
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: NvinitPtrsToken))]
+pub struct InitFunctionTable {
+    #[br(parse_with = read_table_entries, args("init_function_table.entries", ptrs, ptrs.init_function_table_ptr, INIT_FUNCTION_ENTRY_SIZE))]
+    pub entries: Vec<InitFunction>,
+}
+
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InitFunction {
+    pub id: u16,
+    pub script_ptr: u16,
+}
+
+impl InitFunctionTable {
+    pub fn function(&self, id: u16) -> Option<&InitFunction> {
+        self.entries.iter().find(|function| function.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::bit::nvinit::{
+        ConditionTable, InitFunctionTable, MacroIndexTable, MacroTable,
+    };
+    use crate::nvidia::bit::NvinitPtrsToken;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_macro_and_condition_tables() {
+        let mut ptrs: NvinitPtrsToken = Cursor::new(vec![0u8; 34]).read_le().unwrap();
+        ptrs.macro_index_table_ptr = 0;
+        ptrs.macro_table_ptr = 4;
+        ptrs.condition_table_ptr = 20;
+        ptrs.io_condition_table_ptr = 44;
+        let mut bytes = vec![1, 1, 0, 2];
+        for (register, value) in [(0x1000u32, 1u32), (0x2000, 2)] {
+            bytes.extend_from_slice(&register.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for (register, mask, value) in [(0x3000u32, 0xF0u32, 0x10u32), (0x4000, 1, 0)] {
+            bytes.extend_from_slice(&register.to_le_bytes());
+            bytes.extend_from_slice(&mask.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut cursor = Cursor::new(bytes);
+
+        let macro_index_table: MacroIndexTable = cursor.read_le_args((ptrs,)).unwrap();
+        let macro_table: MacroTable = cursor.read_le_args((ptrs,)).unwrap();
+        let condition_table: ConditionTable = cursor.read_le_args((ptrs,)).unwrap();
+
+        assert_eq!(2, macro_index_table.entries.len());
+        assert_eq!(2, macro_table.entries.len());
+        let writes = macro_table.writes(&macro_index_table.entries[0]);
+        assert_eq!(1, writes.len());
+        assert_eq!(0x2000, writes[0].register);
+        assert_eq!(2, macro_table.writes(&macro_index_table.entries[1]).len());
+
+        assert_eq!(2, condition_table.entries.len());
+        assert!(condition_table.entries[0].is_met(0x1F));
+        assert!(!condition_table.entries[0].is_met(0x20));
+        assert!(condition_table.entries[1].is_met(0x2));
+    }
+
+    #[test]
+    fn test_init_function_table() {
+        let mut ptrs: NvinitPtrsToken = Cursor::new(vec![0u8; 34]).read_le().unwrap();
+        ptrs.init_function_table_ptr = 2;
+        ptrs.data_arrays_table_ptr = 10;
+        let mut bytes = vec![0xFF, 0xFF];
+        for (id, script_ptr) in [(1u16, 0x1234u16), (7, 0x5678)] {
+            bytes.extend_from_slice(&id.to_le_bytes());
+            bytes.extend_from_slice(&script_ptr.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0xEE; 4]);
+
+        let table: InitFunctionTable = Cursor::new(bytes).read_le_args((ptrs,)).unwrap();
+
+        assert_eq!(2, table.entries.len());
+        assert_eq!(0x5678, table.function(7).unwrap().script_ptr);
+        assert!(table.function(2).is_none());
+    }
+
+    #[test]
+    fn test_last_table_ends_at_zero_entry() {
+        let mut ptrs: NvinitPtrsToken = Cursor::new(vec![0u8; 34]).read_le().unwrap();
+        ptrs.init_function_table_ptr = 2;
+        let mut bytes = vec![0xFF, 0xFF];
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&0x1234u16.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&[0xEE; 4]);
+
+        let table: InitFunctionTable = Cursor::new(bytes).read_le_args((ptrs,)).unwrap();
+        assert_eq!(1, table.entries.len());
+
+        let bytes = [0xFF, 0xFF, 0x01, 0x00, 0x34, 0x12, 0x02, 0x00];
+        let table: InitFunctionTable = Cursor::new(bytes).read_le_args((ptrs,)).unwrap();
+        assert_eq!(1, table.entries.len());
+    }
+}
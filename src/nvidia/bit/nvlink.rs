@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT
 
+use crate::Error;
 use binread::BinRead;
 use bitflags::bitflags;
 use modular_bitfield::prelude::{B1, B2, B4};
@@ -7,33 +8,7 @@ use modular_bitfield::{bitfield, BitfieldSpecifier};
 use serde::Serialize;
 use std::io::SeekFrom;
 
-bitflags! {
-    impl NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm: u8 {
-        const Rsvd = 0x00;
-        const A0SinglePresent = 0x01;
-        const A1PresentArray = 0x02;
-        const A2FineGrainedExhaustive = 0x04;
-        const A3Rsvd = 0x08;
-        const A4FomCentriod = 0x10;
-        const A5Rsvd = 0x20;
-        const A6Rsvd = 0x40;
-        const A7Rsvd = 0x80;
-    }
-}
-bitflags! {
-    impl NvLinkVbiosParam5Txtrain: u8 {
-        const AdjustmentAlgorithmB0NoAdjustment = 0x10;
-        const AdjustmentAlgorithmB1FixedAdjustment = 0x20;
-        const AdjustmentAlgorithmB2Rsvd = 0x40;
-        const AdjustmentAlgorithmB3Rsvd = 0x80;
-
-        const FomFormatFomA = 0x01;
-        const FomFormatFomB = 0x02;
-        const FomFormatFomC = 0x04;
-    }
-}
-
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: super::NvinitPtrsToken))]
 pub struct NvLinkConfigData {
     #[br(seek_before = SeekFrom::Start(ptrs.nvlink_configuration_data_ptr as u64))]
@@ -43,7 +18,7 @@ pub struct NvLinkConfigData {
     pub entries: Vec<NvLinkEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct NvLinkConfigDataHeader {
     pub version: u8,
     #[br(assert(header_size == 8))]
@@ -51,12 +26,13 @@ pub struct NvLinkConfigDataHeader {
     #[br(assert(base_entry_size == 1))]
     pub base_entry_size: u8,
     pub base_entry_count: u8,
+    #[br(assert(link_entry_size >= 7, Error::InvalidFormat(format!("Unexpected NVLink entry size: {}", link_entry_size))))]
     pub link_entry_size: u8,
     pub link_entry_count: u8,
     pub reserved: u16,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(link_entry_count: u8, link_entry_size: u8))]
 pub struct NvLinkEntry {
     pub position_id: u8,
@@ -65,7 +41,7 @@ pub struct NvLinkEntry {
     pub link_entries: Vec<NvLinkLinkEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(link_entry_size: u8))]
 pub struct NvLinkLinkEntry {
     pub param_0: NvLinkVbiosParam0,
@@ -75,12 +51,12 @@ pub struct NvLinkLinkEntry {
     pub param_4: NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm,
     pub param_5: NvLinkVbiosParam5Txtrain,
     pub param_6: NvLinkVbiosParam6TxtrainMinimumTrainTime,
-    #[br(count(link_entry_size - 7))]
+    #[br(count(link_entry_size.saturating_sub(7)))]
     pub extra_params: Vec<u8>,
 }
 
 #[bitfield]
-#[derive(Copy, Clone, Debug, BinRead, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BinRead, Serialize)]
 #[br(map = Self::from_bytes)]
 pub struct NvLinkVbiosParam0 {
     pub link: bool,
@@ -93,7 +69,7 @@ pub struct NvLinkVbiosParam0 {
     pub reserved_2: B1,
 }
 
-#[derive(Copy, Clone, Debug, BinRead, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BinRead, Serialize)]
 #[repr(u8)]
 #[br(repr = u8)]
 pub enum NvLinkVbiosParam1 {
@@ -108,7 +84,24 @@ pub enum NvLinkVbiosParam1 {
     Unknown0x08,
 }
 
-#[derive(Copy, Clone, Debug, BinRead, Serialize)]
+impl NvLinkVbiosParam1 {
+    /// Line rate in Gbps, the variant names carry it in units of 10 kbps
+    pub fn rate_gbps(&self) -> Option<f64> {
+        match self {
+            NvLinkVbiosParam1::LineRate5_000_000 => Some(50.0),
+            NvLinkVbiosParam1::LineRate1_600_000 => Some(16.0),
+            NvLinkVbiosParam1::LineRate2_000_000 => Some(20.0),
+            NvLinkVbiosParam1::LineRate2_500_000 => Some(25.0),
+            NvLinkVbiosParam1::LineRate2_578_125 => Some(25.78125),
+            NvLinkVbiosParam1::LineRate3_200_000 => Some(32.0),
+            NvLinkVbiosParam1::LineRate4_000_000 => Some(40.0),
+            NvLinkVbiosParam1::LineRate5_312_500 => Some(53.125),
+            NvLinkVbiosParam1::Unknown0x08 => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BinRead, Serialize)]
 #[repr(u8)]
 #[br(repr = u8)]
 pub enum NvLinkVbiosParam2 {
@@ -118,7 +111,7 @@ pub enum NvLinkVbiosParam2 {
 }
 
 #[bitfield]
-#[derive(Copy, Clone, Debug, BinRead, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BinRead, Serialize)]
 pub struct NvLinkVbiosParam3 {
     pub reference_clock_mode: ReferenceClockMode,
     pub reserved_1: B2,
@@ -126,20 +119,45 @@ pub struct NvLinkVbiosParam3 {
     pub reserved_2: B2,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm(u8);
+bitflags! {
+    impl NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm: u8 {
+        const Rsvd = 0x00;
+        const A0SinglePresent = 0x01;
+        const A1PresentArray = 0x02;
+        const A2FineGrainedExhaustive = 0x04;
+        const A3Rsvd = 0x08;
+        const A4FomCentriod = 0x10;
+        const A5Rsvd = 0x20;
+        const A6Rsvd = 0x40;
+        const A7Rsvd = 0x80;
+    }
+}
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct NvLinkVbiosParam5Txtrain(u8);
+bitflags! {
+    impl NvLinkVbiosParam5Txtrain: u8 {
+        const AdjustmentAlgorithmB0NoAdjustment = 0x10;
+        const AdjustmentAlgorithmB1FixedAdjustment = 0x20;
+        const AdjustmentAlgorithmB2Rsvd = 0x40;
+        const AdjustmentAlgorithmB3Rsvd = 0x80;
+
+        const FomFormatFomA = 0x01;
+        const FomFormatFomB = 0x02;
+        const FomFormatFomC = 0x04;
+    }
+}
 
 #[bitfield]
-#[derive(Copy, Clone, Debug, BinRead, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, BinRead, Serialize)]
 pub struct NvLinkVbiosParam6TxtrainMinimumTrainTime {
     pub mantissa: B4,
     pub exponent: B4,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, BitfieldSpecifier, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, BitfieldSpecifier, Serialize)]
 #[bits = 2]
 pub enum ReferenceClockMode {
     Common,
@@ -148,10 +166,71 @@ pub enum ReferenceClockMode {
     NonCommonSs,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, BitfieldSpecifier, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, BitfieldSpecifier, Serialize)]
 #[bits = 2]
 pub enum ClockModeBlockCode {
     Off,
     Ecc96,
     Ecc88,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::bit::nvlink::{
+        NvLinkConfigData, NvLinkVbiosParam1, NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm,
+        NvLinkVbiosParam5Txtrain,
+    };
+    use crate::nvidia::bit::tests::zeroed_token;
+    use crate::nvidia::bit::NvinitPtrsToken;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    fn config_data(link_entry_size: u8) -> NvLinkConfigData {
+        let ptrs: NvinitPtrsToken = zeroed_token();
+        let mut bytes = vec![0x01, 8, 1, 1, link_entry_size, 1, 0, 0];
+        bytes.push(0x00);
+        bytes.extend_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        bytes.extend([0x21, 0x43, 0xAA][..link_entry_size as usize - 7].iter());
+        Cursor::new(bytes).read_le_args((ptrs,)).unwrap()
+    }
+
+    #[test]
+    fn test_txtrain_flags() {
+        let algorithm: NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm =
+            Cursor::new([0x05]).read_le().unwrap();
+        assert_eq!(
+            NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm::A0SinglePresent
+                | NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm::A2FineGrainedExhaustive,
+            algorithm
+        );
+        assert!(!algorithm.contains(NvLinkVbiosParam4TxtrainOptimizatopnAlgorithm::A1PresentArray));
+
+        let txtrain: NvLinkVbiosParam5Txtrain = Cursor::new([0x21]).read_le().unwrap();
+        assert!(txtrain.contains(NvLinkVbiosParam5Txtrain::AdjustmentAlgorithmB1FixedAdjustment));
+        assert!(txtrain.contains(NvLinkVbiosParam5Txtrain::FomFormatFomA));
+        assert!(!txtrain.contains(NvLinkVbiosParam5Txtrain::FomFormatFomB));
+
+        let entry = &config_data(7).entries[0].link_entries[0];
+        assert!(entry.param_4.is_empty());
+        assert!(entry.param_5.is_empty());
+    }
+
+    #[test]
+    fn test_line_rate_gbps() {
+        let entry = &config_data(7).entries[0].link_entries[0];
+
+        assert_eq!(Some(50.0), entry.param_1.rate_gbps());
+        assert_eq!(
+            Some(53.125),
+            NvLinkVbiosParam1::LineRate5_312_500.rate_gbps()
+        );
+        assert_eq!(None, NvLinkVbiosParam1::Unknown0x08.rate_gbps());
+    }
+
+    #[test]
+    fn test_link_entry_without_extra_params() {
+        let entry = &config_data(7).entries[0].link_entries[0];
+
+        assert!(entry.extra_params.is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use super::PerfPtrsToken;
+use crate::Error;
 use binread::BinRead;
 use modular_bitfield::bitfield;
 use modular_bitfield::prelude::*;
@@ -36,31 +37,35 @@ use std::io::SeekFrom;
 //     pub unk: [u8; 11],
 // }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: PerfPtrsToken))]
 pub struct MemoryClockTable {
     #[br(seek_before = SeekFrom::Start(ptrs.memory_clock_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("memory_clock_table.header", ()))]
     pub header: MemoryClockTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptrs.memory_clock_table_ptr as u64 + header.header_size as u64))]
     #[br(count(header.entry_count))]
-    #[br(args(header.base_entry_size, header.strap_entry_size, header.strap_entry_count))]
+    #[br(parse_with = crate::traced, args("memory_clock_table.entries", (header.base_entry_size, header.strap_entry_size, header.strap_entry_count)))]
     pub entries: Vec<MemoryClockTableEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
-#[repr(packed)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MemoryClockTableHeader {
     //#[br(assert(version == 0x20))]
     pub version: u8,
-    #[br(assert(header_size == 26))]
-    pub header_size: u8,
-    pub base_entry_size: u8,   // 86
-    pub strap_entry_size: u8,  // 44
+    #[br(assert(header_size >= 6, Error::InvalidFormat(format!("Unexpected memory clock table header size: {}", header_size))))]
+    pub header_size: u8, // 26
+    #[br(assert(base_entry_size >= 8, Error::InvalidFormat(format!("Unexpected memory clock table base entry size: {}", base_entry_size))))]
+    pub base_entry_size: u8, // 86
+    #[br(assert(strap_entry_size >= 11, Error::InvalidFormat(format!("Unexpected memory clock table strap entry size: {}", strap_entry_size))))]
+    pub strap_entry_size: u8, // 44
     pub strap_entry_count: u8, // 14
     pub entry_count: u8,       // 10
-    pub unknown: [u8; 20],
+    #[br(parse_with = crate::read_remaining, args("Memory clock table header", header_size, 6))]
+    pub unknown: Vec<u8>, // todo
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(base_entry_size: u8, strap_entry_size: u8, strap_entry_count: u8))]
 pub struct MemoryClockTableEntry {
     #[br(args(base_entry_size))]
@@ -70,20 +75,20 @@ pub struct MemoryClockTableEntry {
     pub strap_entries: Vec<MemoryClockTableStrapEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(base_entry_size: u8))]
 pub struct MemoryClockTableBaseEntry {
-    #[br(map(|v: u16| v & 0x3F))]
+    #[br(map(|v: u16| v & 0x3FFF))]
     pub min_freq: u16,
-    #[br(map(|v: u16| v & 0x3F))]
+    #[br(map(|v: u16| v & 0x3FFF))]
     pub max_freq: u16,
     pub reserved: [u8; 4],
 
-    #[br(count(base_entry_size - 8))]
+    #[br(parse_with = crate::read_remaining, args("Memory clock table base entry", base_entry_size, 8))]
     pub unknown: Vec<u8>, // todo
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(strap_entry_size: u8))]
 pub struct MemoryClockTableStrapEntry {
     pub mem_tweak_index: u8,
@@ -93,30 +98,32 @@ pub struct MemoryClockTableStrapEntry {
     pub reserved_1: u8,
     pub flags_5: u8,
 
-    #[br(count(strap_entry_size - 11))]
+    #[br(parse_with = crate::read_remaining, args("Memory clock table strap entry", strap_entry_size, 11))]
     pub unknown: Vec<u8>, //todo
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: PerfPtrsToken))]
 pub struct PowerPolicyTable {
     #[br(seek_before = SeekFrom::Start(ptrs.power_policy_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("power_policy_table.header", ()))]
     pub header: PowerPolicyTableHeader,
     #[br(seek_before = SeekFrom::Start(ptrs.power_policy_table_ptr as u64 + header.header_size as u64))]
     #[br(count(header.entry_count))]
+    #[br(parse_with = crate::traced, args("power_policy_table.entries", ()))]
     pub entries: Vec<PowerPolicyTableEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PowerPolicyTableHeader {
-    #[br(assert(version == 0x30))]
+    #[br(assert(version == 0x30, Error::InvalidFormat(format!("Unsupported power policy table version: {:#X}", version))))]
     pub version: u8,
     pub header_size: u8,
     pub entry_size: u8,
     pub entry_count: u8,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PowerPolicyTableEntry {
     pub unk_0: u16,
     pub min: u32,
@@ -127,27 +134,125 @@ pub struct PowerPolicyTableEntry {
     pub unk_2: Vec<u8>,
 }
 
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: PerfPtrsToken))]
+pub struct ThermalChannelTable {
+    #[br(seek_before = SeekFrom::Start(ptrs.thermal_channel_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("thermal_channel_table.header", ()))]
+    pub header: ThermalChannelTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptrs.thermal_channel_table_ptr as u64 + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    #[br(parse_with = crate::traced, args("thermal_channel_table.entries", (header.entry_size,)))]
+    pub entries: Vec<ThermalChannelTableEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ThermalChannelTableHeader {
+    pub version: u8,
+    #[br(assert(header_size >= 4, Error::InvalidFormat(format!("Unexpected thermal channel table header size: {}", header_size))))]
+    pub header_size: u8,
+    #[br(assert(entry_size >= 3, Error::InvalidFormat(format!("Unexpected thermal channel table entry size: {}", entry_size))))]
+    pub entry_size: u8,
+    pub entry_count: u8,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(entry_size: u8))]
+pub struct ThermalChannelTableEntry {
+    pub class: u8,
+    pub thermal_device_index: u8,
+    pub provider_index: u8,
+    #[br(parse_with = crate::read_remaining, args("Thermal channel table entry", entry_size, 3))]
+    pub unknown: Vec<u8>, // todo
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(ptrs: PerfPtrsToken))]
+pub struct ThermalPolicyTable {
+    #[br(seek_before = SeekFrom::Start(ptrs.thermal_policy_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("thermal_policy_table.header", ()))]
+    pub header: ThermalPolicyTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptrs.thermal_policy_table_ptr as u64 + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    #[br(parse_with = crate::traced, args("thermal_policy_table.entries", (header.entry_size,)))]
+    pub entries: Vec<ThermalPolicyTableEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ThermalPolicyTableHeader {
+    pub version: u8,
+    #[br(assert(header_size >= 4, Error::InvalidFormat(format!("Unexpected thermal policy table header size: {}", header_size))))]
+    pub header_size: u8,
+    #[br(assert(entry_size >= 3, Error::InvalidFormat(format!("Unexpected thermal policy table entry size: {}", entry_size))))]
+    pub entry_size: u8,
+    pub entry_count: u8,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(entry_size: u8))]
+pub struct ThermalPolicyTableEntry {
+    pub policy_index: u8,
+    pub thermal_channel_index: u8,
+    pub flags: u8,
+    #[br(parse_with = crate::read_remaining, args("Thermal policy table entry", entry_size, 3))]
+    pub unknown: Vec<u8>, // todo
+}
+
+/// Table with the common version, header size, entry size and entry count header whose
+/// entries aren't decoded, `name` identifies the table in the trace and the errors
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(table_ptr: u64, name: &'static str))]
+pub struct RawTable {
+    #[br(seek_before = SeekFrom::Start(table_ptr))]
+    #[br(parse_with = crate::traced, args(name, (name,)))]
+    pub header: RawTableHeader,
+    #[br(seek_before = SeekFrom::Start(table_ptr + header.header_size as u64))]
+    #[br(count(header.entry_count))]
+    #[br(parse_with = crate::traced, args(name, (header.entry_size,)))]
+    pub entries: Vec<RawTableEntry>,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(name: &'static str))]
+pub struct RawTableHeader {
+    pub version: u8,
+    #[br(assert(header_size >= 4, Error::InvalidFormat(format!("Unexpected {} header size: {}", name, header_size))))]
+    pub header_size: u8,
+    #[br(assert(entry_size > 0, Error::InvalidFormat(format!("Unexpected {} entry size: {}", name, entry_size))))]
+    pub entry_size: u8,
+    pub entry_count: u8,
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
+#[br(import(entry_size: u8))]
+pub struct RawTableEntry {
+    #[br(count(entry_size))]
+    pub unknown: Vec<u8>, // todo
+}
+
 // https://nvidia.github.io/open-gpu-doc/virtual-p-state-table/virtual-P-state-table.html
 // https://docs.nvidia.com/gameworks/content/gameworkslibrary/coresdk/nvapi/group__gpupstate.html
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: PerfPtrsToken))]
 pub struct VirtualPStateTable20 {
     #[br(seek_before = SeekFrom::Start(ptrs.virtual_p_state_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("virtual_p_state_table.header", ()))]
     pub header: VirtualPStateTableHeader20,
     #[br(count(header.entry_count))]
-    #[br(args(header.domain_freq_entry_count))]
+    #[br(parse_with = crate::traced, args("virtual_p_state_table.entries", (header.domain_freq_entry_count,)))]
     pub entries: Vec<VirtualPStateTableEntry20>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VirtualPStateTableHeader20 {
-    #[br(assert(version == 0x20))]
+    #[br(assert(version == 0x20, Error::InvalidFormat(format!("Unsupported virtual P-state table version: {:#X}", version))))]
     pub version: u8,
+    #[br(assert(header_size >= 6, Error::InvalidFormat(format!("Unexpected virtual P-state table header size: {}", header_size))))]
     pub header_size: u8,
-    #[br(assert(base_entry_size == 1))]
+    #[br(assert(base_entry_size == 1, Error::InvalidFormat(format!("Unexpected virtual P-state table base entry size: {}", base_entry_size))))]
     pub base_entry_size: u8,
     pub entry_count: u8,
-    #[br(assert(domain_freq_entry_size == 4))]
+    #[br(assert(domain_freq_entry_size == 4, Error::InvalidFormat(format!("Unexpected virtual P-state table domain entry size: {}", domain_freq_entry_size))))]
     pub domain_freq_entry_size: u8,
     pub domain_freq_entry_count: u8,
 
@@ -164,11 +269,11 @@ pub struct VirtualPStateTableHeader20 {
     // max_batt_entry
     // unk15_entry
     // unk16_entry
-    #[br(count(header_size - 6))]
+    #[br(parse_with = crate::read_remaining, args("Virtual P-state table header", header_size, 6))]
     pub p_state_indexes: Vec<u8>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(domain_freq_entry_count: u8))]
 pub struct VirtualPStateTableEntry20 {
     pub p_state: u8,
@@ -182,7 +287,7 @@ pub struct VirtualPStateTableEntry20 {
     pub domains_entries: Vec<VirtualPStateTableDomainEntry20>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VirtualPStateTableDomainEntry20 {
     #[br(restore_position)]
     #[br(map(|v: u8| [v & 0x8 > 0, v & 0x4 > 0]))]
@@ -196,31 +301,35 @@ pub struct VirtualPStateTableDomainEntry20 {
     pub frequency_2: u32,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(ptrs: PerfPtrsToken))]
 pub struct MemoryTweakTable {
     #[br(seek_before = SeekFrom::Start(ptrs.memory_tweak_table_ptr as u64))]
+    #[br(parse_with = crate::traced, args("memory_tweak_table.header", ()))]
     pub header: MemoryTweakTableHeader,
+    #[br(seek_before = SeekFrom::Start(ptrs.memory_tweak_table_ptr as u64 + header.header_size as u64))]
     #[br(count(header.entry_count))]
-    #[br(args(header.extended_entry_count))]
+    #[br(parse_with = crate::traced, args("memory_tweak_table.entries", (header.extended_entry_count,)))]
     pub entries: Vec<MemoryTweakTableEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MemoryTweakTableHeader {
-    #[br(assert(version == 0x20))]
+    #[br(assert(version == 0x20, Error::InvalidFormat(format!("Unsupported memory tweak table version: {:#X}", version))))]
     pub version: u8,
-    #[br(assert(header_size == 6))]
+    #[br(assert(header_size >= 6, Error::InvalidFormat(format!("Unexpected memory tweak table header size: {}", header_size))))]
     pub header_size: u8,
-    #[br(assert(base_entry_size == 76))]
+    #[br(assert(base_entry_size == 76, Error::InvalidFormat(format!("Unexpected memory tweak table base entry size: {}", base_entry_size))))]
     pub base_entry_size: u8,
-    #[br(assert(extended_entry_size == 12))]
+    #[br(assert(extended_entry_size == 12, Error::InvalidFormat(format!("Unexpected memory tweak table extended entry size: {}", extended_entry_size))))]
     pub extended_entry_size: u8,
     pub extended_entry_count: u8,
     pub entry_count: u8,
+    #[br(parse_with = crate::read_remaining, args("Memory tweak table header", header_size, 6))]
+    pub unknown: Vec<u8>, // todo
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 #[br(import(extended_entry_count: u8))]
 pub struct MemoryTweakTableEntry {
     pub base_entry: MemoryTweakTableBaseEntry,
@@ -228,7 +337,25 @@ pub struct MemoryTweakTableEntry {
     pub extended_entries: Vec<MemoryTweakTableExtendedEntry>,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+impl MemoryTweakTableEntry {
+    /// Straps of the memory clock table entry that use this tweak entry. `position` is the
+    /// position of the entry in the table, the `mem_tweak_index` the straps refer to it with.
+    pub fn semantic_index(
+        &self,
+        position: usize,
+        memory_clock_entry: &MemoryClockTableEntry,
+    ) -> Vec<usize> {
+        memory_clock_entry
+            .strap_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, strap_entry)| strap_entry.mem_tweak_index as usize == position)
+            .map(|(strap, _)| strap)
+            .collect()
+    }
+}
+
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MemoryTweakTableBaseEntry {
     pub config_0: MemoryTweakTableBaseEntryConfig0,
     pub config_1: MemoryTweakTableBaseEntryConfig1,
@@ -246,7 +373,7 @@ pub struct MemoryTweakTableBaseEntry {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig0 {
     pub rc: u8,
     pub rfc: B9,
@@ -256,7 +383,7 @@ pub struct MemoryTweakTableBaseEntryConfig0 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig1 {
     pub cl: B7,
     pub wl: B7,
@@ -266,7 +393,7 @@ pub struct MemoryTweakTableBaseEntryConfig1 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig2 {
     pub rpre: B4,
     pub wpre: B4,
@@ -279,7 +406,7 @@ pub struct MemoryTweakTableBaseEntryConfig2 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig3 {
     pub pdex: B5,
     pub pden2pdex: B4,
@@ -290,7 +417,7 @@ pub struct MemoryTweakTableBaseEntryConfig3 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig4 {
     pub refresh_lo: B3,
     pub refresh: B12,
@@ -300,7 +427,7 @@ pub struct MemoryTweakTableBaseEntryConfig4 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryConfig5 {
     pub adr_min: B3,
     pub reserved_6: B1,
@@ -314,7 +441,7 @@ pub struct MemoryTweakTableBaseEntryConfig5 {
 }
 
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryVoltageConfig {
     pub drive_strength: B2,
     pub voltage_0: B3,
@@ -335,16 +462,312 @@ pub struct MemoryTweakTableBaseEntryVoltageConfig {
     pub reserved_3: B36,
 }
 
+/// Raw voltage codes of the memory rails.
+/// The rail mapping isn't documented by NVIDIA, it follows the order assumed by community memory timing editors:
+/// core (VDD), I/O (VDDQ), pump (VPP) and then the reference voltages. Treat it as tentative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VoltageSummary {
+    pub vdd: u8,
+    pub vddq: u8,
+    pub vpp: u8,
+    pub mem_vref: u8,
+    pub ca_vref: u8,
+    pub dq_vref: u8,
+}
+
+impl MemoryTweakTableBaseEntryVoltageConfig {
+    pub fn vdd(&self) -> u8 {
+        self.voltage_0()
+    }
+
+    pub fn vddq(&self) -> u8 {
+        self.voltage_1()
+    }
+
+    pub fn vpp(&self) -> u8 {
+        self.voltage_2()
+    }
+
+    pub fn mem_vref(&self) -> u8 {
+        self.voltage_3()
+    }
+
+    pub fn ca_vref(&self) -> u8 {
+        self.voltage_4()
+    }
+
+    pub fn dq_vref(&self) -> u8 {
+        self.voltage_5()
+    }
+}
+
+impl MemoryTweakTable {
+    /// Entries with their positions. The position is the `mem_tweak_index` of the memory clock
+    /// strap entries, it isn't the strap id, use [`MemoryTweakTable::entry_for_strap`] instead.
+    pub fn indexed_entries(&self) -> impl Iterator<Item = (usize, &MemoryTweakTableEntry)> {
+        self.entries.iter().enumerate()
+    }
+
+    /// The tweak entry of the memory strap, resolved through the memory clock table entry
+    pub fn entry_for_strap(
+        &self,
+        memory_clock_entry: &MemoryClockTableEntry,
+        strap: usize,
+    ) -> Option<&MemoryTweakTableEntry> {
+        let strap_entry = memory_clock_entry.strap_entries.get(strap)?;
+        self.entries.get(strap_entry.mem_tweak_index as usize)
+    }
+
+    /// Straps of the memory clock table entry that use the tweak entry at the position
+    pub fn straps_for_entry(
+        &self,
+        memory_clock_entry: &MemoryClockTableEntry,
+        position: usize,
+    ) -> Vec<usize> {
+        self.entries
+            .get(position)
+            .map(|entry| entry.semantic_index(position, memory_clock_entry))
+            .unwrap_or_default()
+    }
+
+    /// Voltages of the tweak entry of every strap, keyed by the maximum frequency of its memory
+    /// clock table entry. Ordered by the memory clock entries and then by their straps.
+    pub fn strap_voltages(
+        &self,
+        memory_clock_table: &MemoryClockTable,
+    ) -> Vec<(u16, VoltageSummary)> {
+        memory_clock_table
+            .entries
+            .iter()
+            .flat_map(|memory_clock_entry| {
+                (0..memory_clock_entry.strap_entries.len()).filter_map(move |strap| {
+                    self.entry_for_strap(memory_clock_entry, strap)
+                        .map(|entry| {
+                            (
+                                memory_clock_entry.base_entry.max_freq,
+                                entry.base_entry.voltages(),
+                            )
+                        })
+                })
+            })
+            .collect()
+    }
+}
+
+impl MemoryTweakTableBaseEntry {
+    pub fn voltages(&self) -> VoltageSummary {
+        let config = &self.voltage_config;
+        VoltageSummary {
+            vdd: config.vdd(),
+            vddq: config.vddq(),
+            vpp: config.vpp(),
+            mem_vref: config.mem_vref(),
+            ca_vref: config.ca_vref(),
+            dq_vref: config.dq_vref(),
+        }
+    }
+}
+
 #[bitfield]
-#[derive(BinRead, Debug, Clone, Serialize, BitfieldSpecifier)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize, BitfieldSpecifier)]
 pub struct MemoryTweakTableBaseEntryTiming22 {
     pub rfcsba: B10,
     pub rfcsbr: B8,
     pub reserved: B14,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MemoryTweakTableExtendedEntry {
     #[br(count(12))]
     pub unknown: Vec<u8>,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::bit::perf::{
+        MemoryClockTable, MemoryClockTableBaseEntry, MemoryTweakTable,
+        MemoryTweakTableBaseEntryVoltageConfig, RawTable, ThermalChannelTableEntry, VoltageSummary,
+    };
+    use crate::nvidia::bit::tests::zeroed_token;
+    use crate::nvidia::bit::PerfPtrsToken;
+    use crate::Error;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_voltage_config_names() {
+        let config = MemoryTweakTableBaseEntryVoltageConfig::from_bytes([
+            0x44, 0x03, 0x54, 0x06, 0, 0, 0, 0, 0,
+        ]);
+
+        assert_eq!(
+            VoltageSummary {
+                vdd: 1,
+                vddq: 2,
+                vpp: 3,
+                mem_vref: 4,
+                ca_vref: 5,
+                dq_vref: 6,
+            },
+            VoltageSummary {
+                vdd: config.vdd(),
+                vddq: config.vddq(),
+                vpp: config.vpp(),
+                mem_vref: config.mem_vref(),
+                ca_vref: config.ca_vref(),
+                dq_vref: config.dq_vref(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_raw_table() {
+        let bytes = vec![0xFF, 0xFF, 0x10, 5, 3, 2, 0xEE, 1, 2, 3, 4, 5, 6];
+        let table: RawTable = Cursor::new(bytes)
+            .read_le_args((2, "ventura table"))
+            .unwrap();
+
+        assert_eq!(0x10, table.header.version);
+        assert_eq!(
+            vec![vec![1, 2, 3], vec![4, 5, 6]],
+            table
+                .entries
+                .iter()
+                .map(|e| e.unknown.clone())
+                .collect::<Vec<_>>()
+        );
+
+        let err = Cursor::new(vec![0x10, 4, 0, 1])
+            .read_le_args::<RawTable>((0, "ventura table"))
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(message)) if message == "Unexpected ventura table entry size: 0"
+        ));
+    }
+
+    #[test]
+    fn test_memory_tweak_entry_for_strap() {
+        let ptrs: PerfPtrsToken = zeroed_token();
+        let mut clock_bytes = vec![0x20, 6, 10, 11, 3, 1];
+        clock_bytes.extend_from_slice(&[0u8; 10]);
+        for mem_tweak_index in [1, 0, 1] {
+            clock_bytes.push(mem_tweak_index);
+            clock_bytes.extend_from_slice(&[0u8; 10]);
+        }
+        let memory_clock_table: MemoryClockTable =
+            Cursor::new(clock_bytes).read_le_args((ptrs,)).unwrap();
+        let memory_clock_entry = &memory_clock_table.entries[0];
+
+        let mut tweak_bytes = vec![0x20, 6, 76, 12, 0, 2];
+        tweak_bytes.extend_from_slice(&[0u8; 76]);
+        let mut second_entry = vec![0u8; 76];
+        second_entry[0] = 0x42;
+        tweak_bytes.extend(second_entry);
+        let memory_tweak_table: MemoryTweakTable =
+            Cursor::new(tweak_bytes).read_le_args((ptrs,)).unwrap();
+
+        assert_eq!(2, memory_tweak_table.indexed_entries().count());
+        let entry = memory_tweak_table
+            .entry_for_strap(memory_clock_entry, 0)
+            .unwrap();
+        assert_eq!(0x42, entry.base_entry.config_0.rc());
+        let (position, entry) = memory_tweak_table.indexed_entries().nth(1).unwrap();
+        assert_eq!(
+            vec![0, 2],
+            entry.clone().semantic_index(position, memory_clock_entry)
+        );
+        let (position, entry) = memory_tweak_table.indexed_entries().next().unwrap();
+        assert_eq!(vec![1], entry.semantic_index(position, memory_clock_entry));
+        assert!(memory_tweak_table
+            .entry_for_strap(memory_clock_entry, 3)
+            .is_none());
+        assert_eq!(
+            vec![0, 2],
+            memory_tweak_table.straps_for_entry(memory_clock_entry, 1)
+        );
+    }
+
+    #[test]
+    fn test_memory_strap_voltages() {
+        let ptrs: PerfPtrsToken = zeroed_token();
+        let mut clock_bytes = vec![0x20, 6, 10, 11, 2, 1];
+        clock_bytes.extend_from_slice(&0x100u16.to_le_bytes());
+        clock_bytes.extend_from_slice(&0x2710u16.to_le_bytes());
+        clock_bytes.extend_from_slice(&[0u8; 6]);
+        for mem_tweak_index in [1, 2] {
+            clock_bytes.push(mem_tweak_index);
+            clock_bytes.extend_from_slice(&[0u8; 10]);
+        }
+        let memory_clock_table: MemoryClockTable =
+            Cursor::new(clock_bytes).read_le_args((ptrs,)).unwrap();
+
+        let mut tweak_bytes = vec![0x20, 6, 76, 12, 0, 2];
+        tweak_bytes.extend_from_slice(&[0u8; 76]);
+        let mut second_entry = vec![0u8; 76];
+        second_entry[47] = 0x44;
+        tweak_bytes.extend(second_entry);
+        let memory_tweak_table: MemoryTweakTable =
+            Cursor::new(tweak_bytes).read_le_args((ptrs,)).unwrap();
+
+        let voltages = memory_tweak_table.strap_voltages(&memory_clock_table);
+        assert_eq!(1, voltages.len());
+        assert_eq!(0x2710, voltages[0].0);
+        assert_eq!(1, voltages[0].1.vdd);
+        assert_eq!(2, voltages[0].1.vddq);
+    }
+
+    #[test]
+    fn test_memory_clock_frequency_mask() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(0x4000u16 | 405).to_le_bytes());
+        bytes.extend_from_slice(&(0xC000u16 | 0x2710).to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 4]);
+        bytes.extend_from_slice(&[0x03, 0x11]);
+        let entry: MemoryClockTableBaseEntry = Cursor::new(bytes).read_le_args((10,)).unwrap();
+
+        assert_eq!(405, entry.min_freq);
+        assert_eq!(0x2710, entry.max_freq);
+        assert_eq!(vec![0x03, 0x11], entry.unknown);
+    }
+
+    #[test]
+    fn test_memory_clock_table_without_entries() {
+        let ptrs: PerfPtrsToken = zeroed_token();
+        let bytes = vec![0x20, 8, 86, 44, 14, 0, 0xAA, 0xBB];
+        let table: MemoryClockTable = Cursor::new(bytes).read_le_args((ptrs,)).unwrap();
+
+        assert!(table.entries.is_empty());
+        assert_eq!(vec![0xAA, 0xBB], table.header.unknown);
+    }
+
+    #[test]
+    fn test_memory_clock_table_wrong_header_size() {
+        let ptrs: PerfPtrsToken = zeroed_token();
+        let bytes = vec![0x20, 4, 86, 44, 14, 0];
+        let err = Cursor::new(bytes)
+            .read_le_args::<MemoryClockTable>((ptrs,))
+            .unwrap_err();
+
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_undersized_entry_remainder() {
+        let err = Cursor::new([0x01, 0x02, 0x03, 0x04])
+            .read_le_args::<ThermalChannelTableEntry>((2,))
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+
+        let entry = Cursor::new([0x01, 0x02, 0x03, 0x04])
+            .read_le_args::<ThermalChannelTableEntry>((4,))
+            .unwrap();
+        assert_eq!(vec![0x04], entry.unknown);
+    }
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT
 
+use crate::Error;
 use binread::BinRead;
 use bitflags::bitflags;
 use modular_bitfield::prelude::*;
@@ -12,14 +13,57 @@ pub const DCB_SIGNATURE: &[u8] = b"\xcb\xbd\xdc\x4e";
 pub struct DeviceControlBlock {
     #[br(parse_with = crate::stream_position)]
     pub offset_in_region: u64,
+    /// Firmware offset of the region the structure was found in
+    #[br(default)]
+    pub region_offset_in_firmware: Option<u64>,
     #[br(restore_position)]
     pub header: DeviceControlBlockHeader,
     #[br(pad_before(header.header_size as i64))]
-    pub unknown: u8,
     #[br(count(header.entry_count))]
+    #[br(args(header.entry_size))]
     pub entries: Vec<DeviceEntry>,
 }
 
+impl DeviceControlBlock {
+    /// The first device entry before the end of the list that is neither skipped, virtual,
+    /// nor removed from the boot devices
+    pub fn boot_display(&self) -> Option<&DeviceEntry> {
+        self.entries
+            .iter()
+            .map_while(
+                |entry| match entry.display_path_information.display_type_or_err() {
+                    Ok(DisplayType::EndOfLine) => None,
+                    display_type => Some((entry, display_type)),
+                },
+            )
+            .find(|(entry, display_type)| {
+                let info = &entry.display_path_information;
+                !matches!(display_type, Ok(DisplayType::SkipEntry) | Err(_))
+                    && !info.is_virtual_device()
+                    && !info.is_boot_device_removed()
+            })
+            .map(|(entry, _)| entry)
+    }
+
+    /// Highest link rate among the DisplayPort devices before the end of the list
+    pub fn max_dp_link_rate(&self) -> Option<MaximumLinkRate> {
+        self.entries
+            .iter()
+            .map_while(
+                |entry| match entry.display_path_information.display_type_or_err() {
+                    Ok(DisplayType::EndOfLine) => None,
+                    display_type => Some((entry, display_type)),
+                },
+            )
+            .filter(|(_, display_type)| matches!(display_type, Ok(DisplayType::DisplayPort)))
+            .filter_map(|(entry, _)| match &entry.device_specific_information {
+                DeviceSpecificInformation::Dfp(info) => info.maximum_link_rate_or_err().ok(),
+                _ => None,
+            })
+            .max_by_key(|rate| rate.mbps())
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct DeviceControlBlockHeader {
     #[br(parse_with = crate::stream_position)]
@@ -27,6 +71,7 @@ pub struct DeviceControlBlockHeader {
     pub version: u8,
     pub header_size: u8,
     pub entry_count: u8,
+    #[br(assert(entry_size >= 8, Error::InvalidFormat(format!("Unexpected DCB entry size: {}", entry_size))))]
     pub entry_size: u8,
     pub communications_control_block_pointer: u16,
     #[br(assert(signature == DCB_SIGNATURE))]
@@ -59,19 +104,29 @@ bitflags! {
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
+#[br(import(entry_size: u8))]
 pub struct DeviceEntry {
-    #[br(restore_position)]
-    #[br(pad_before(4))]
     pub display_path_information: DisplayPathInformation,
 
     #[br(args(display_path_information.display_type()))]
-    #[br(pad_after(4))]
+    #[br(pad_after(entry_size as i64 - 8))]
     pub device_specific_information: DeviceSpecificInformation,
 }
 
+impl DeviceEntry {
+    /// External communication port selected by DFP and TV devices
+    pub fn external_communication_port(&self) -> Option<ExternalCommunicationsPort> {
+        match &self.device_specific_information {
+            DeviceSpecificInformation::Dfp(info) => Some(info.external_communication_port()),
+            DeviceSpecificInformation::Tv(info) => Some(info.external_communication_port()),
+            _ => None,
+        }
+    }
+}
+
 #[bitfield]
 #[derive(Copy, Clone, Debug, BinRead, Serialize)]
-#[br(map = |value: u32| Self::from_bytes(value.to_be_bytes()))]
+#[br(map = |value: u32| Self::from_bytes(value.to_le_bytes()))]
 pub struct DisplayPathInformation {
     pub display_type: DisplayType,
     pub edid_port: B4,
@@ -123,12 +178,12 @@ pub enum DeviceSpecificInformation {
 
 #[bitfield]
 #[derive(Copy, Clone, Debug, BinRead, Serialize)]
-#[br(map = |value: u32| Self::from_bytes(value.to_be_bytes()))]
+#[br(map = |value: u32| Self::from_bytes(value.to_le_bytes()))]
 pub struct DfpDeviceSpecificInformation {
     pub edid_source: EdidSource,
     pub power_and_backlight_control: PowerAndBacklightControl,
-    pub sub_link_b_dp_b_pad_link_1: bool,
     pub sub_link_a_dp_a_pad_link_0: bool,
+    pub sub_link_b_dp_b_pad_link_1: bool,
     pub reserved_4: B2,
 
     pub external_link_type: ExternalLinkType,
@@ -194,6 +249,18 @@ pub enum MaximumLinkRate {
     Rate8100Mbps = 0x3,
 }
 
+impl MaximumLinkRate {
+    /// Link rate per lane
+    pub fn mbps(&self) -> u32 {
+        match self {
+            MaximumLinkRate::Rate1620Mbps => 1620,
+            MaximumLinkRate::Rate2700Mbps => 2700,
+            MaximumLinkRate::Rate5400Mbps => 5400,
+            MaximumLinkRate::Rate8100Mbps => 8100,
+        }
+    }
+}
+
 #[derive(Debug, Clone, BitfieldSpecifier, Serialize)]
 #[bits = 4]
 pub enum MaximumLaneCount {
@@ -205,11 +272,14 @@ pub enum MaximumLaneCount {
 }
 
 fn map_tv_device_specification_information(value: u32) -> TvDeviceSpecificInformation {
-    let bytes = value.to_be_bytes();
-    let dacs: u8 = bytes[0] & 0x0F + bytes[2] & 0xF0;
-    // [sdtv:3, rsvd:1, e:1, cc: 2, hdtv: 4, rsvd: 5, dacs: 8, encoder: 8]
-    let bytes = [bytes[0] & 0xF0 + bytes[2] & 0x0F, bytes[3], dacs, bytes[1]];
-    TvDeviceSpecificInformation::from_bytes(bytes)
+    // [sdtv: 3, rsvd: 1, dacs_lo: 4, encoder: 8, e: 1, cc: 2, hdtv: 4, rsvd: 5, dacs_hi: 4]
+    let sdtv = value & 0xF;
+    let e_cc_hdtv = (value >> 16) & 0xFFF;
+    let dacs = ((value >> 4) & 0xF) | (((value >> 28) & 0xF) << 4);
+    let encoder = (value >> 8) & 0xFF;
+    // [sdtv: 3, rsvd: 1, e: 1, cc: 2, hdtv: 4, rsvd: 5, dacs: 8, encoder: 8]
+    let value = sdtv | (e_cc_hdtv << 4) | (dacs << 16) | (encoder << 24);
+    TvDeviceSpecificInformation::from_bytes(value.to_le_bytes())
 }
 
 #[bitfield]
@@ -330,10 +400,10 @@ pub struct GpioAssignmentTable {
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct GpioAssignmentTableHeader {
     pub version: u8,
-    #[br(assert(header_size >= 6))]
+    #[br(assert(header_size >= 6, Error::InvalidFormat(format!("GPIO assignment table header size {} is smaller than its 6 fixed bytes", header_size))))]
     pub header_size: u8,
     pub entry_count: u8,
-    #[br(assert(entry_size >= 5))]
+    #[br(assert(entry_size >= 5, Error::InvalidFormat(format!("GPIO assignment table entry size {} is smaller than its 5 fixed bytes", entry_size))))]
     pub entry_size: u8,
     #[br(pad_after = header_size as i64 - 6)]
     pub ext_gpio_master: u16,
@@ -362,7 +432,7 @@ pub struct GpioEntryPin {
 }
 
 // More: https://nvidia.github.io/open-gpu-doc/DCB/DCB-4.x-Specification.html
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[repr(u8)]
 #[br(repr = u8)]
 pub enum GpioEntryFunction {
@@ -445,18 +515,20 @@ pub struct I2cDevicesTable {
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct I2cDevicesTableHeader {
     pub version: u8,
-    #[br(assert(header_size >= 5))]
+    #[br(assert(header_size >= 5, Error::InvalidFormat(format!("I2C devices table header size {} is smaller than its 5 fixed bytes", header_size))))]
     pub header_size: u8,
     pub entry_count: u8,
-    #[br(assert(entry_size == 4))]
+    #[br(assert(entry_size == 4, Error::InvalidFormat(format!("Unexpected I2C devices table entry size: {}", entry_size))))]
     pub entry_size: u8,
     #[br(pad_after = header_size as i64 - 5)]
     pub flags: I2cDevicesTableHeaderFlags,
 }
 
+// The entry is a little-endian dword with the device type in bits 7:0, so it's read without a map.
+// Unverified: the bit order follows the DCB specification, no ROM with a populated I2C devices
+// table has been checked yet.
 #[bitfield]
 #[derive(BinRead, Debug, Clone, Serialize)]
-//#[br(map = |value: u32| Self::from_bytes(value.to_be_bytes()))]
 pub struct I2cDevicesTableEntry {
     pub device_type: I2cDevicesTableEntryDeviceType,
     pub i2c_address: u8,
@@ -467,6 +539,17 @@ pub struct I2cDevicesTableEntry {
     pub reserved_1: B5,
 }
 
+impl I2cDevicesTableEntry {
+    /// I2C bus the device is connected to
+    pub fn bus(&self) -> ExternalCommunicationsPort {
+        if self.external_communications_port() == 0 {
+            ExternalCommunicationsPort::Primary
+        } else {
+            ExternalCommunicationsPort::Secondary
+        }
+    }
+}
+
 #[derive(Debug, Clone, BitfieldSpecifier, Serialize)]
 #[bits = 8]
 pub enum I2cDevicesTableEntryDeviceType {
@@ -509,13 +592,154 @@ pub struct ConnectorTable {
     pub entries: Vec<ConnectorTableEntry>,
 }
 
+impl ConnectorTable {
+    /// Connectors present for the dock state, the `*IfDocked` and `*IfNotDocked` entries
+    /// are kept only when they match it
+    pub fn effective_connectors(&self, docked: bool) -> Vec<ConnectorTableEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| match entry.connector_type_or_err() {
+                Ok(ConnectorType::SkipEntry) | Err(_) => false,
+                Ok(connector_type) => connector_type
+                    .dock_condition()
+                    .is_none_or(|if_docked| if_docked == docked),
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn physical_connectors(&self) -> Vec<PhysicalConnector> {
+        let mut connectors: Vec<PhysicalConnector> = Vec::new();
+        for entry in &self.entries {
+            let connector_type = match entry.connector_type_or_err() {
+                Ok(ConnectorType::SkipEntry) | Err(_) => continue,
+                Ok(connector_type) => connector_type,
+            };
+            let location = entry.location();
+            let index = match connectors.iter().position(|c| c.location == location) {
+                Some(index) => index,
+                None => {
+                    connectors.push(PhysicalConnector {
+                        location,
+                        label: String::new(),
+                        output_kinds: vec![],
+                        connector_types: vec![],
+                    });
+                    connectors.len() - 1
+                }
+            };
+            let connector = &mut connectors[index];
+            let output_kind = connector_type.output_kind();
+            if !connector.output_kinds.contains(&output_kind) {
+                connector.output_kinds.push(output_kind);
+            }
+            connector.connector_types.push(connector_type);
+        }
+
+        let mut label_counts: Vec<(String, usize)> = Vec::new();
+        for connector in &mut connectors {
+            let kinds_label = connector
+                .output_kinds
+                .iter()
+                .map(|kind| kind.name())
+                .collect::<Vec<_>>()
+                .join("/");
+            let number = match label_counts.iter_mut().find(|(l, _)| *l == kinds_label) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    label_counts.push((kinds_label.clone(), 1));
+                    1
+                }
+            };
+            connector.label = format!("{} #{}", kinds_label, number);
+        }
+        connectors
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhysicalConnector {
+    pub location: u8,
+    pub label: String,
+    pub output_kinds: Vec<ConnectorOutputKind>,
+    pub connector_types: Vec<ConnectorType>,
+}
+
+/// Outputs the board can drive, a physical connector is counted once for each of its kinds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DisplayCapabilities {
+    pub display_port: usize,
+    pub hdmi: usize,
+    pub dvi: usize,
+    pub vga: usize,
+    pub internal_panel: usize,
+    /// Highest DisplayPort link rate per lane in Mbps
+    pub max_dp_link_rate_mbps: Option<u32>,
+}
+
+impl DisplayCapabilities {
+    pub fn new(
+        device_control_block: Option<&DeviceControlBlock>,
+        connector_table: Option<&ConnectorTable>,
+    ) -> Self {
+        let mut capabilities = DisplayCapabilities {
+            max_dp_link_rate_mbps: device_control_block
+                .and_then(|dcb| dcb.max_dp_link_rate())
+                .map(|rate| rate.mbps()),
+            ..Default::default()
+        };
+        let connectors = connector_table
+            .map(|table| table.physical_connectors())
+            .unwrap_or_default();
+        for kind in connectors.iter().flat_map(|c| &c.output_kinds) {
+            match kind {
+                ConnectorOutputKind::DisplayPort => capabilities.display_port += 1,
+                ConnectorOutputKind::Hdmi => capabilities.hdmi += 1,
+                ConnectorOutputKind::Dvi => capabilities.dvi += 1,
+                ConnectorOutputKind::Vga => capabilities.vga += 1,
+                ConnectorOutputKind::InternalPanel => capabilities.internal_panel += 1,
+                ConnectorOutputKind::Tv | ConnectorOutputKind::Other => {}
+            }
+        }
+        capabilities
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConnectorOutputKind {
+    DisplayPort,
+    Hdmi,
+    Dvi,
+    Vga,
+    Tv,
+    InternalPanel,
+    Other,
+}
+
+impl ConnectorOutputKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConnectorOutputKind::DisplayPort => "DisplayPort",
+            ConnectorOutputKind::Hdmi => "HDMI",
+            ConnectorOutputKind::Dvi => "DVI",
+            ConnectorOutputKind::Vga => "VGA",
+            ConnectorOutputKind::Tv => "TV",
+            ConnectorOutputKind::InternalPanel => "Internal Panel",
+            ConnectorOutputKind::Other => "Other",
+        }
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct ConnectorTableHeader {
     pub version: u8,
-    #[br(assert(header_size >= 5))]
+    #[br(assert(header_size >= 5, Error::InvalidFormat(format!("Connector table header size {} is smaller than its 5 fixed bytes", header_size))))]
     pub header_size: u8,
     pub entry_count: u8,
-    #[br(assert(entry_size == 4))]
+    #[br(assert(entry_size == 4, Error::InvalidFormat(format!("Unexpected connector table entry size: {}", entry_size))))]
     pub entry_size: u8,
     pub platform: ConnectorTablePlatform,
 }
@@ -548,6 +772,68 @@ pub struct ConnectorTableEntry {
     pub reserved: B1,
 }
 
+impl ConnectorTableEntry {
+    pub fn hotplug_lines(&self) -> Vec<HotplugLine> {
+        [
+            (HotplugLine::A, self.hotplug_a_interrupt()),
+            (HotplugLine::B, self.hotplug_b_interrupt()),
+            (HotplugLine::C, self.hotplug_c_interrupt()),
+            (HotplugLine::D, self.hotplug_d_interrupt()),
+            (HotplugLine::E, self.hotplug_e_interrupt()),
+            (HotplugLine::F, self.hotplug_f_interrupt()),
+            (HotplugLine::G, self.hotplug_g_interrupt()),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(line, _)| line)
+        .collect()
+    }
+
+    /// Resolves the GPIO pin of each hotplug interrupt line used by the connector
+    pub fn hotplug_gpio_pins(
+        &self,
+        gpio_assignment_table: &GpioAssignmentTable,
+    ) -> Vec<(HotplugLine, Option<u8>)> {
+        self.hotplug_lines()
+            .into_iter()
+            .map(|line| {
+                let function = line.gpio_function();
+                let pin = gpio_assignment_table
+                    .entries
+                    .iter()
+                    .find(|entry| entry.function == Some(function))
+                    .map(|entry| entry.pin.pin_number());
+                (line, pin)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HotplugLine {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+}
+
+impl HotplugLine {
+    pub fn gpio_function(&self) -> GpioEntryFunction {
+        match self {
+            HotplugLine::A => GpioEntryFunction::HotPlugA,
+            HotplugLine::B => GpioEntryFunction::HotPlugB,
+            HotplugLine::C => GpioEntryFunction::HotPlugC,
+            HotplugLine::D => GpioEntryFunction::HotPlugD,
+            HotplugLine::E => GpioEntryFunction::HotPlugE,
+            HotplugLine::F => GpioEntryFunction::HotPlugF,
+            HotplugLine::G => GpioEntryFunction::HotPlugG,
+        }
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 #[br(repr = u8)]
 #[repr(u8)]
@@ -620,6 +906,80 @@ pub enum ConnectorType {
     SkipEntry = 0xFF,
 }
 
+impl ConnectorType {
+    /// `Some(true)` for connectors present only when docked, `Some(false)` for connectors
+    /// present only when not docked
+    pub fn dock_condition(&self) -> Option<bool> {
+        match self {
+            ConnectorType::Vga15PinIfDocked
+            | ConnectorType::DviIIfDocked
+            | ConnectorType::DviDIfDocked
+            | ConnectorType::DisplayPortExternalIfDocked
+            | ConnectorType::DisplayPortMiniExternalIfDocked => Some(true),
+            ConnectorType::Vga15PinIfNotDocked
+            | ConnectorType::DviIIfNotDocked
+            | ConnectorType::DviDIfNotDocked
+            | ConnectorType::DisplayPortExternalIfNotDocked
+            | ConnectorType::DisplayPortMiniExternalIfNotDocked => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn output_kind(&self) -> ConnectorOutputKind {
+        match self {
+            ConnectorType::Vga15Pin
+            | ConnectorType::PodVga15Pin
+            | ConnectorType::Bnc
+            | ConnectorType::Vga15PinIfNotDocked
+            | ConnectorType::Vga15PinIfDocked => ConnectorOutputKind::Vga,
+            ConnectorType::DviA
+            | ConnectorType::DviITvSVideo
+            | ConnectorType::DviITvComposite
+            | ConnectorType::DviITvSVideoBreakoutComposite
+            | ConnectorType::DviI
+            | ConnectorType::DviD
+            | ConnectorType::AppleDisplayConnector
+            | ConnectorType::LfhDviI1
+            | ConnectorType::LfhDviI2
+            | ConnectorType::DviIIfNotDocked
+            | ConnectorType::DviIIfDocked
+            | ConnectorType::DviDIfNotDocked
+            | ConnectorType::DviDIfDocked => ConnectorOutputKind::Dvi,
+            ConnectorType::TvCompositeOut
+            | ConnectorType::TvSVideoOut
+            | ConnectorType::TvSVideoBreakoutComposite
+            | ConnectorType::TvHdtvComponentYPrPb
+            | ConnectorType::TvScart
+            | ConnectorType::TvCompositeScartOverBlue
+            | ConnectorType::TvHdtvEiaj4120
+            | ConnectorType::PodHdtvYPrPb
+            | ConnectorType::PodSVideo
+            | ConnectorType::PodComposite => ConnectorOutputKind::Tv,
+            ConnectorType::LvdsSpwgAttached
+            | ConnectorType::LvdsOemAttached
+            | ConnectorType::LvdsSpwgDetached
+            | ConnectorType::LvdsOemDetached
+            | ConnectorType::TmdsOemAttached
+            | ConnectorType::DisplayPortInternalConnector => ConnectorOutputKind::InternalPanel,
+            ConnectorType::DisplayPortExternalConnector
+            | ConnectorType::DisplayPortMiniExternalConnector
+            | ConnectorType::DisplayPortExternalIfNotDocked
+            | ConnectorType::DisplayPortExternalIfDocked
+            | ConnectorType::DisplayPortMiniExternalIfNotDocked
+            | ConnectorType::DisplayPortMiniExternalIfDocked
+            | ConnectorType::LfhDp1
+            | ConnectorType::LfhDp2 => ConnectorOutputKind::DisplayPort,
+            ConnectorType::HdmiAConnector | ConnectorType::HdmiCMiniConnector => {
+                ConnectorOutputKind::Hdmi
+            }
+            ConnectorType::ThreePinDinStereoConnector
+            | ConnectorType::AudioSpdifConnector
+            | ConnectorType::VirtualConnectorForWifiDisplay
+            | ConnectorType::SkipEntry => ConnectorOutputKind::Other,
+        }
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct CommunicationsControlBlock {
     #[br(restore_position)]
@@ -633,9 +993,10 @@ pub struct CommunicationsControlBlock {
 pub struct CommunicationsControlBlockHeader {
     #[br(assert(version == 0x41))]
     pub version: u8,
+    #[br(assert(header_size >= 5, Error::InvalidFormat(format!("CCB header size {} is smaller than its 5 fixed bytes", header_size))))]
     pub header_size: u8,
     pub entry_count: u8,
-    #[br(assert(entry_size == 4))]
+    #[br(assert(entry_size == 4, Error::InvalidFormat(format!("Unexpected CCB entry size: {}", entry_size))))]
     pub entry_size: u8,
     #[br(restore_position)]
     pub primary_communication_port: u8,
@@ -651,6 +1012,54 @@ pub struct CommunicationsControlBlockEntry {
     pub i2c_port_speed: CommunicationsControlBlockI2cPortSpeed,
 }
 
+const CCB_UNUSED_PORT: u8 = 0x1F;
+
+impl CommunicationsControlBlock {
+    pub fn entry(&self, index: u8) -> Option<&CommunicationsControlBlockEntry> {
+        self.entries.get(index as usize)
+    }
+
+    /// CCB entry the primary or secondary communication port refers to
+    pub fn resolve_port(
+        &self,
+        port: &ExternalCommunicationsPort,
+    ) -> Option<&CommunicationsControlBlockEntry> {
+        self.entry(self.header.port_index(port))
+    }
+
+    /// CCB entry of the external communication port of a DFP or TV device
+    pub fn device_port(&self, device: &DeviceEntry) -> Option<&CommunicationsControlBlockEntry> {
+        self.resolve_port(&device.external_communication_port()?)
+    }
+
+    /// CCB entry used to read the EDID of a device
+    pub fn edid_port(&self, device: &DeviceEntry) -> Option<&CommunicationsControlBlockEntry> {
+        self.entry(device.display_path_information.edid_port())
+    }
+}
+
+impl CommunicationsControlBlockHeader {
+    /// Both port indexes share one byte, the primary port in the low nibble
+    pub fn port_index(&self, port: &ExternalCommunicationsPort) -> u8 {
+        match port {
+            ExternalCommunicationsPort::Primary => self.primary_communication_port & 0x0F,
+            ExternalCommunicationsPort::Secondary => self.secondary_communication_port >> 4,
+        }
+    }
+}
+
+impl CommunicationsControlBlockEntry {
+    /// Physical I2C bus, `None` if the entry has no I2C port
+    pub fn i2c_bus(&self) -> Option<u8> {
+        (self.i2c_port() != CCB_UNUSED_PORT).then_some(self.i2c_port())
+    }
+
+    /// Physical DisplayPort AUX channel, `None` if the entry has no AUX port
+    pub fn dp_aux_bus(&self) -> Option<u8> {
+        (self.dp_aux_port() != CCB_UNUSED_PORT).then_some(self.dp_aux_port())
+    }
+}
+
 #[derive(Debug, Clone, BitfieldSpecifier, Serialize)]
 #[bits = 4]
 pub enum CommunicationsControlBlockI2cPortSpeed {
@@ -664,3 +1073,279 @@ pub enum CommunicationsControlBlockI2cPortSpeed {
     Speed60kHz,
     Speed300kHz,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::dcb::{
+        CommunicationsControlBlock, ConnectorCount, ConnectorTable, ConnectorTableEntry,
+        ConnectorTableHeader, Dacs, DeviceControlBlock, DfpDeviceSpecificInformation,
+        DisplayCapabilities, DisplayPathInformation, DisplayType, EdidSource, EncoderIdentifier,
+        ExternalCommunicationsPort, ExternalLinkType, GpioAssignmentTable, HdtvFormat, HotplugLine,
+        I2cDevicesTable, I2cDevicesTableEntry, I2cDevicesTableEntryDeviceType, Location,
+        MaximumLaneCount, MaximumLinkRate, PowerAndBacklightControl, SdtvFormat,
+        TvDeviceSpecificInformation, DCB_SIGNATURE,
+    };
+    use crate::Error;
+    use binread::BinReaderExt;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ccb_ports() {
+        // Primary port 1, secondary port 0
+        let mut bytes = vec![0x41, 5, 2, 4, 0x01];
+        let unused = 0x1Fu32 | (0x1F << 5);
+        bytes.extend_from_slice(&(0x02u32 | (0x1F << 5)).to_le_bytes());
+        bytes.extend_from_slice(&(0x03u32 | (0x04 << 5)).to_le_bytes());
+        bytes.extend_from_slice(&unused.to_le_bytes());
+        let ccb: CommunicationsControlBlock = Cursor::new(bytes).read_le().unwrap();
+
+        assert_eq!(2, ccb.entries.len());
+        let primary = ccb
+            .resolve_port(&ExternalCommunicationsPort::Primary)
+            .unwrap();
+        assert_eq!(Some(3), primary.i2c_bus());
+        assert_eq!(Some(4), primary.dp_aux_bus());
+        let secondary = ccb
+            .resolve_port(&ExternalCommunicationsPort::Secondary)
+            .unwrap();
+        assert_eq!(Some(2), secondary.i2c_bus());
+        assert_eq!(None, secondary.dp_aux_bus());
+        assert!(ccb.entry(2).is_none());
+    }
+
+    #[test]
+    fn test_undersized_table_headers() {
+        let err = Cursor::new([0x41, 4, 0, 5, 0, 0, 0, 0, 0, 0])
+            .read_le::<GpioAssignmentTable>()
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+
+        let err = Cursor::new([0x40, 3, 0, 4, 0, 0, 0, 0])
+            .read_le::<I2cDevicesTable>()
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+
+        let err = Cursor::new([0x40, 4, 0, 4, 0, 0, 0, 0])
+            .read_le::<ConnectorTableHeader>()
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_display_path_information() {
+        let value: u32 =
+            0x6 | (0x2 << 4) | (0x1 << 8) | (0x3 << 12) | (0x4 << 16) | (0x1 << 20) | (0x2 << 24);
+        let info = Cursor::new(value.to_le_bytes())
+            .read_le::<DisplayPathInformation>()
+            .unwrap();
+
+        assert_eq!(DisplayType::DisplayPort, info.display_type());
+        assert_eq!(2, info.edid_port());
+        assert_eq!(1, info.head());
+        assert_eq!(3, info.connector());
+        assert_eq!(4, info.bus());
+        assert!(matches!(info.location(), Location::OnBoard));
+        assert!(!info.is_boot_device_removed());
+        assert!(!info.is_blind_boot_device_removed());
+        assert_eq!(2, info.output_devices());
+        assert!(!info.is_virtual_device());
+    }
+
+    #[test]
+    fn test_dfp_device_specific_information() {
+        let value: u32 = 0x2
+            | (0x1 << 2)
+            | (0x1 << 4)
+            | (0x0C << 8)
+            | (0x1 << 17)
+            | (0x1 << 20)
+            | (0x3 << 21)
+            | (0x4 << 24);
+        let info = Cursor::new(value.to_le_bytes())
+            .read_le::<DfpDeviceSpecificInformation>()
+            .unwrap();
+
+        assert!(matches!(info.edid_source(), EdidSource::DdcAcpiOrBiosCalls));
+        assert!(matches!(
+            info.power_and_backlight_control(),
+            PowerAndBacklightControl::Scripts
+        ));
+        assert!(info.sub_link_a_dp_a_pad_link_0());
+        assert!(!info.sub_link_b_dp_b_pad_link_1());
+        assert!(matches!(
+            info.external_link_type(),
+            ExternalLinkType::ParadeTechDp5014LaneDisplayPort
+        ));
+        assert!(info.hdmi_enable());
+        assert!(matches!(
+            info.external_communication_port(),
+            ExternalCommunicationsPort::Secondary
+        ));
+        assert!(matches!(
+            info.maximum_link_rate(),
+            MaximumLinkRate::Rate8100Mbps
+        ));
+        assert!(matches!(
+            info.maximum_lane_count(),
+            MaximumLaneCount::FourLines
+        ));
+    }
+
+    #[test]
+    fn test_tv_device_specific_information() {
+        let value: u32 =
+            0x2 | (0x3 << 4) | (0x46 << 8) | (0x1 << 16) | (0x1 << 17) | (0x4 << 19) | (0x1 << 28);
+        let info = Cursor::new(value.to_le_bytes())
+            .read_le::<TvDeviceSpecificInformation>()
+            .unwrap();
+
+        assert!(matches!(info.sdtv_format(), SdtvFormat::PalM));
+        assert!(matches!(info.dacs(), Dacs::SVideoOnRedAndGreen));
+        assert!(matches!(
+            info.encoder_identifier(),
+            EncoderIdentifier::Chrontel7009
+        ));
+        assert!(matches!(
+            info.external_communication_port(),
+            ExternalCommunicationsPort::Secondary
+        ));
+        assert!(matches!(
+            info.connection_count(),
+            ConnectorCount::TwoConnectors
+        ));
+        assert!(matches!(info.hdtv_format(), HdtvFormat::Hdtv720P60Hz));
+    }
+
+    // Entry built from the DCB specification bit layout, not taken from a ROM
+    #[test]
+    fn test_i2c_devices_table_entry_spec_layout() {
+        let value: u32 = 0x0A | (0x9A << 8) | (0x1 << 20) | (0x1 << 21) | (0x2 << 24);
+        let entry = Cursor::new(value.to_le_bytes())
+            .read_le::<I2cDevicesTableEntry>()
+            .unwrap();
+
+        assert!(matches!(
+            entry.device_type(),
+            I2cDevicesTableEntryDeviceType::Adt7473
+        ));
+        assert_eq!(0x9A, entry.i2c_address());
+        assert_eq!(1, entry.external_communications_port());
+        assert!(matches!(entry.bus(), ExternalCommunicationsPort::Secondary));
+        assert_eq!(1, entry.write_access_privilege_level());
+        assert_eq!(2, entry.read_access_privilege_level());
+    }
+
+    fn device_control_block(entries: &[u32]) -> DeviceControlBlock {
+        let entries: Vec<(u32, u32)> = entries.iter().map(|entry| (*entry, 0)).collect();
+        device_control_block_with_info(&entries)
+    }
+
+    fn device_control_block_with_info(entries: &[(u32, u32)]) -> DeviceControlBlock {
+        let mut bytes = vec![0x40, 27, entries.len() as u8, 8, 0, 0];
+        bytes.extend_from_slice(DCB_SIGNATURE);
+        bytes.extend_from_slice(&[0u8; 17]);
+        for (entry, info) in entries {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+            bytes.extend_from_slice(&info.to_le_bytes());
+        }
+        Cursor::new(bytes).read_le().unwrap()
+    }
+
+    #[test]
+    fn test_display_capabilities() {
+        let dcb = device_control_block_with_info(&[
+            (0x6, 0),
+            (0x6, 0x2 << 21),
+            (0x2, 0x3 << 21),
+            (0xE, 0),
+            (0x6, 0x3 << 21),
+        ]);
+        assert!(matches!(
+            dcb.max_dp_link_rate(),
+            Some(MaximumLinkRate::Rate5400Mbps)
+        ));
+        let table: ConnectorTable = Cursor::new([
+            0x40, 5, 3, 4, 0x00, // header
+            0x46, 0x00, 0x00, 0x00, // DisplayPort
+            0x61, 0x01, 0x00, 0x00, // HDMI
+            0x46, 0x02, 0x00, 0x00, // DisplayPort
+        ])
+        .read_le()
+        .unwrap();
+
+        assert_eq!(
+            DisplayCapabilities {
+                display_port: 2,
+                hdmi: 1,
+                max_dp_link_rate_mbps: Some(5400),
+                ..Default::default()
+            },
+            DisplayCapabilities::new(Some(&dcb), Some(&table))
+        );
+        assert_eq!(
+            DisplayCapabilities::default(),
+            DisplayCapabilities::new(None, None)
+        );
+    }
+
+    #[test]
+    fn test_boot_display() {
+        let dcb = device_control_block(&[0x6 | 1 << 22, 0xF, 0x2 | 1 << 28, 0x2 | 1 << 12, 0xE]);
+        let boot_display = dcb.boot_display().unwrap();
+
+        assert_eq!(1, boot_display.display_path_information.connector());
+
+        let dcb = device_control_block(&[0x6 | 1 << 22, 0xE, 0x2]);
+        assert!(dcb.boot_display().is_none());
+    }
+
+    #[test]
+    fn test_connector_hotplug_gpio_pins() {
+        let entry: ConnectorTableEntry = Cursor::new([0x31, 0x10, 0x01, 0x00]).read_le().unwrap();
+        let gpio_assignment_table: GpioAssignmentTable = Cursor::new([
+            0x41, 6, 2, 5, 0, 0, // header
+            0x05, 7, 0, 0, 0, // HotPlugA on pin 5
+            0x0A, 9, 0, 0, 0, // FanControl on pin 10
+        ])
+        .read_le()
+        .unwrap();
+
+        assert_eq!(vec![HotplugLine::A, HotplugLine::C], entry.hotplug_lines());
+        assert_eq!(
+            vec![(HotplugLine::A, Some(5)), (HotplugLine::C, None)],
+            entry.hotplug_gpio_pins(&gpio_assignment_table)
+        );
+    }
+
+    #[test]
+    fn test_effective_connectors() {
+        let table: ConnectorTable = Cursor::new([
+            0x40, 5, 4, 4, 0x18, // header
+            0x47, 0x00, 0x00, 0x00, // internal DisplayPort
+            0x56, 0x01, 0x00, 0x00, // external DisplayPort if not docked
+            0x57, 0x02, 0x00, 0x00, // external DisplayPort if docked
+            0xFF, 0x00, 0x00, 0x00, // skip entry
+        ])
+        .read_le()
+        .unwrap();
+
+        let locations = |docked| {
+            table
+                .effective_connectors(docked)
+                .iter()
+                .map(|entry| entry.location())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![0, 2], locations(true));
+        assert_eq!(vec![0, 1], locations(false));
+    }
+}
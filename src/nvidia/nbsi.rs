@@ -1,11 +1,13 @@
 // SPDX-License-Identifier: MIT
 
 use crate::pci_legacy::PciExpansionRomDataHeader;
-use crate::{FirmwareRegion, FIRMWARE_REGION_ALIGN};
-use binread::BinRead;
+use crate::{Error, FirmwareRegion, VersionHex4};
+use binread::{BinRead, BinReaderExt, BinResult, ReadOptions};
 use derivative::Derivative;
+use log::warn;
 use serde::Serialize;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{Read, Seek, SeekFrom};
 use std::mem::size_of;
 use strum::FromRepr;
 
@@ -16,10 +18,13 @@ pub const NBSI_SIGNATURE: &[u8] = b"ISBN";
 #[derive(BinRead, Derivative, Clone, Serialize)]
 #[derivative(Debug)]
 pub struct NbsiPciExpansionRom {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: NbsiPciExpansionRomHeader,
+    #[br(parse_with = crate::check_signature, args(offset_in_firmware, header.pcir_offset, crate::nvidia::NV_PCI_DATA_STRUCTURE_SIGNATURE))]
+    #[derivative(Debug = "ignore")]
+    #[serde(skip)]
+    _pcir_signature: (),
     #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware + header.pcir_offset as u64))]
     #[br(assert(data_header.signature == crate::nvidia::NV_PCI_DATA_STRUCTURE_SIGNATURE))]
     pub data_header: PciExpansionRomDataHeader,
@@ -28,6 +33,32 @@ pub struct NbsiPciExpansionRom {
     pub data_header_extended: Option<crate::nvidia::NvidiaPciDataExtended>,
     #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware + header.nbsi_data_offset as u64))]
     pub nbsi_directory: NbsiDirectory,
+    #[br(parse_with = read_plat_info, args(nbsi_directory.object(GlobalType::PlatInfo).map(|o| (o.data_offset_in_region, o.data_size))))]
+    pub plat_info: Option<NbsiPlatInfo>,
+    /// Workaround copy of the PlatInfo object
+    #[br(parse_with = read_plat_info, args(nbsi_directory.object(GlobalType::PlatInfoWar).map(|o| (o.data_offset_in_region, o.data_size))))]
+    pub plat_info_war: Option<NbsiPlatInfo>,
+}
+
+fn read_plat_info<R: Read + Seek>(
+    reader: &mut R,
+    _ro: &ReadOptions,
+    (data,): (Option<(u64, u64)>,),
+) -> BinResult<Option<NbsiPlatInfo>> {
+    let Some((data_offset, data_size)) = data else {
+        return Ok(None);
+    };
+    reader.seek(SeekFrom::Start(data_offset))?;
+    match reader.read_le_args((data_size,)) {
+        Ok(plat_info) => Ok(Some(plat_info)),
+        Err(err) => {
+            warn!(
+                "Failed to parse NBSI PlatInfo object at {}: {}",
+                data_offset, err
+            );
+            Ok(None)
+        }
+    }
 }
 
 impl FirmwareRegion for NbsiPciExpansionRom {
@@ -38,6 +69,10 @@ impl FirmwareRegion for NbsiPciExpansionRom {
     fn region_size(&self) -> u64 {
         self.data_header.image_length as u64 * 512
     }
+
+    fn kind(&self) -> &'static str {
+        "nbsi"
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
@@ -67,6 +102,31 @@ pub struct NbsiDirectory {
     pub objects: Vec<NbsiGenericObject>,
 }
 
+impl NbsiDirectory {
+    pub fn object(&self, global_type: GlobalType) -> Option<&NbsiGenericObject> {
+        self.objects
+            .iter()
+            .find(|object| { object.header.global_type } == global_type.to_repr())
+    }
+}
+
+const MAX_PLAT_INFO_SIZE: u64 = 0x1000;
+
+// Tentative layout of the PlatInfo object data, only the subsystem id is decoded. The board id and
+// the SKU are not documented and stay in the unknown fields
+#[derive(BinRead, Debug, Clone, Serialize)]
+#[br(import(data_size: u64))]
+pub struct NbsiPlatInfo {
+    #[br(assert(
+        (8..=MAX_PLAT_INFO_SIZE).contains(&data_size),
+        Error::InvalidFormat(format!("Unexpected NBSI PlatInfo size: {}", data_size))
+    ))]
+    pub unknown1: u32,
+    pub subsystem_id: VersionHex4,
+    #[br(count(data_size - 8))]
+    pub unknown2: Vec<u8>,
+}
+
 #[derive(BinRead, Clone, Serialize)]
 pub struct NbsiGlobal(u16);
 
@@ -85,7 +145,7 @@ impl Debug for NbsiGlobal {
     }
 }
 
-#[derive(BinRead, Debug, Clone, Serialize, FromRepr)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize, FromRepr)]
 #[repr(u16)]
 #[br(repr = u16)]
 pub enum GlobalType {
@@ -108,10 +168,44 @@ pub enum GlobalType {
     OptimusPlat = u16::from_le_bytes(*b"OP"),
 }
 
+impl GlobalType {
+    pub fn to_repr(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Two-letter code used in the NVIDIA documentation, e.g. `VB`
+    pub fn code(&self) -> [u8; 2] {
+        self.to_repr().to_le_bytes()
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let bytes: [u8; 2] = code.as_bytes().try_into().ok()?;
+        Self::from_repr(u16::from_le_bytes(bytes))
+    }
+}
+
+impl Display for GlobalType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let code = self.code();
+        if code.iter().all(|b| b.is_ascii_alphabetic()) {
+            write!(f, "{}", String::from_utf8_lossy(&code))
+        } else {
+            write!(f, "{:?}", self)
+        }
+    }
+}
+
 #[derive(BinRead, Debug, Clone, Serialize)]
 pub struct NbsiGenericObject {
     #[br(parse_with = crate::stream_position)]
     pub offset_in_region: u64,
+    #[br(assert(
+        header.size as u64 >= size_of::<NbsiGenericObjectHeader>() as u64,
+        Error::InvalidFormat(format!(
+            "NBSI object size {} is smaller than its header",
+            { header.size }
+        ))
+    ))]
     pub header: NbsiGenericObjectHeader,
     #[br(calc(header.size as u64 - size_of::<NbsiGenericObjectHeader>() as u64))]
     pub data_size: u64,
@@ -129,3 +223,84 @@ pub struct NbsiGenericObjectHeader {
     pub min_version: u8,
     pub max_version: u8,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::nvidia::nbsi::{read_plat_info, GlobalType, NbsiDirectory, NbsiGenericObject};
+    use crate::Error;
+    use binread::{BinReaderExt, ReadOptions};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_undersized_generic_object() {
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(b"VB");
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        let err = Cursor::new(data)
+            .read_le::<NbsiGenericObject>()
+            .unwrap_err();
+        assert!(matches!(
+            err.custom_err::<Error>(),
+            Some(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_plat_info() {
+        let mut data = b"ISBN".to_vec();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&[1, 0]);
+        data.extend_from_slice(b"PI");
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(b"PI");
+        data.extend_from_slice(&29u32.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&0xA1u32.to_le_bytes());
+        data.extend_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        data.extend_from_slice(&0x2204u16.to_le_bytes());
+        data.extend_from_slice(&0x0300u16.to_le_bytes());
+        data.push(0xEE);
+
+        let mut cursor = Cursor::new(data);
+        let directory: NbsiDirectory = cursor.read_le().unwrap();
+        let object = directory.object(GlobalType::PlatInfo).unwrap();
+        assert!(directory.object(GlobalType::VBios).is_none());
+
+        let plat_info = read_plat_info(
+            &mut cursor,
+            &ReadOptions::default(),
+            (Some((object.data_offset_in_region, object.data_size)),),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(&[0x12, 0x34, 0x56, 0x78], plat_info.subsystem_id.as_bytes());
+        assert_eq!(vec![0x04, 0x22, 0x00, 0x03, 0xEE], plat_info.unknown2);
+        assert!(read_plat_info(
+            &mut cursor,
+            &ReadOptions::default(),
+            (Some((object.data_offset_in_region, 4)),),
+        )
+        .unwrap()
+        .is_none());
+        assert!(
+            read_plat_info(&mut cursor, &ReadOptions::default(), (None,))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_global_type_code() {
+        assert_eq!(*b"VB", GlobalType::VBios.code());
+        assert_eq!("VB", GlobalType::VBios.to_string());
+        assert_eq!("Reserved", GlobalType::Reserved.to_string());
+        assert_eq!(Some(GlobalType::OptimusPlat), GlobalType::from_code("OP"));
+        assert_eq!(None, GlobalType::from_code("XX"));
+        assert_eq!(
+            Some(GlobalType::Hdcp),
+            GlobalType::from_repr(GlobalType::Hdcp.to_repr())
+        );
+    }
+}
@@ -5,7 +5,7 @@ use crate::pci_legacy::{
     PciExpansionRomDataHeader, PCI_EXPANSION_ROM_DATA_IDENTIFIER,
     PCI_EXPANSION_ROM_HEADER_IDENTIFIER,
 };
-use crate::{FirmwareRegion, FIRMWARE_REGION_ALIGN};
+use crate::FirmwareRegion;
 use binread::io::SeekFrom;
 use binread::BinRead;
 use derivative::Derivative;
@@ -15,24 +15,33 @@ const EFI_SIGNATURE: &[u8] = b"\xf1\x0e\0\0";
 
 #[derive(BinRead, Derivative, Clone, Serialize)]
 #[derivative(Debug)]
+#[br(import(max_image_bytes: u64))]
 pub struct EfiPciExpansionRom {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: EfiPciExpansionRomHeader,
+    #[br(parse_with = crate::check_signature, args(offset_in_firmware, header.pcir_offset, PCI_EXPANSION_ROM_DATA_IDENTIFIER))]
+    #[derivative(Debug = "ignore")]
+    #[serde(skip)]
+    _pcir_signature: (),
     #[br(seek_before = SeekFrom::Start(header.pcir_offset as u64 + offset_in_firmware))]
     #[br(assert(data_header.signature == PCI_EXPANSION_ROM_DATA_IDENTIFIER))]
     pub data_header: PciExpansionRomDataHeader,
     #[br(align_before = 16)]
     #[br(try)]
     pub data_header_extended: Option<NvidiaPciDataExtended>,
-    #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware))]
-    #[br(count(data_header.image_length))]
+    #[br(parse_with = crate::read_image_data, args(offset_in_firmware, data_header.image_length, max_image_bytes))]
     #[derivative(Debug = "ignore")]
     #[serde(skip)]
     pub data: Vec<u8>,
 }
 
+impl EfiPciExpansionRom {
+    pub fn machine_type(&self) -> EfiPciExpansionRomMachineType {
+        self.header.efi_machine_type
+    }
+}
+
 impl FirmwareRegion for EfiPciExpansionRom {
     fn offset_in_firmware(&self) -> u64 {
         self.offset_in_firmware
@@ -41,6 +50,10 @@ impl FirmwareRegion for EfiPciExpansionRom {
     fn region_size(&self) -> u64 {
         self.data_header.image_length as u64 * 512
     }
+
+    fn kind(&self) -> &'static str {
+        "efi"
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
@@ -66,7 +79,7 @@ pub enum EfiPciExpansionRomSubsystem {
     RuntimeDriver = 0x0C,
 }
 
-#[derive(BinRead, Debug, Clone, Serialize)]
+#[derive(BinRead, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[repr(u16)]
 #[br(repr = u16)]
 pub enum EfiPciExpansionRomMachineType {
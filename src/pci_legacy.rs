@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::nvidia::NvidiaPciDataExtended;
-use crate::{FirmwareRegion, FIRMWARE_REGION_ALIGN};
+use crate::FirmwareRegion;
 use binread::BinRead;
 use derivative::Derivative;
 use serde::Serialize;
@@ -9,26 +9,42 @@ use serde::Serialize;
 pub const PCI_EXPANSION_ROM_HEADER_IDENTIFIER: &[u8] = b"\x55\xAA";
 pub const PCI_EXPANSION_ROM_DATA_IDENTIFIER: &[u8] = b"PCIR";
 
+/// Structure pointers inside the image, such as the BIT and DCB ones, are relative to the
+/// start of the legacy image, see `FirmwareInfo::standalone_legacy_image`
 #[derive(BinRead, Derivative, Clone, Serialize)]
 #[derivative(Debug)]
+#[br(import(max_image_bytes: u64))]
 pub struct PciExpansionRom {
-    #[br(align_before = FIRMWARE_REGION_ALIGN)]
     #[br(parse_with = crate::stream_position)]
     pub offset_in_firmware: u64,
     pub header: PciExpansionRomHeader,
+    #[br(parse_with = crate::check_signature, args(offset_in_firmware, header.pcir_offset, PCI_EXPANSION_ROM_DATA_IDENTIFIER))]
+    #[derivative(Debug = "ignore")]
+    #[serde(skip)]
+    _pcir_signature: (),
     #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware + header.pcir_offset as u64))]
     #[br(assert(data_header.signature == PCI_EXPANSION_ROM_DATA_IDENTIFIER))]
     pub data_header: PciExpansionRomDataHeader,
     #[br(align_before = 16)]
     #[br(try)]
     pub data_header_extended: Option<NvidiaPciDataExtended>,
-    #[br(seek_before = binread::io::SeekFrom::Start(offset_in_firmware))]
-    #[br(count(data_header.image_length))]
+    #[br(parse_with = crate::read_image_data, args(offset_in_firmware, data_header.image_length, max_image_bytes))]
     #[derivative(Debug = "ignore")]
     #[serde(skip)]
     pub data: Vec<u8>,
 }
 
+impl PciExpansionRom {
+    /// Open Firmware and PA-RISC images carry big-endian code instead of the x86 BIOS
+    pub fn is_open_firmware(&self) -> bool {
+        matches!(
+            self.data_header.code_type,
+            PciExpansionRomCodeType::OpenFirmwareStandardForPci
+                | PciExpansionRomCodeType::HewlettPackardPaRisc
+        )
+    }
+}
+
 impl FirmwareRegion for PciExpansionRom {
     fn offset_in_firmware(&self) -> u64 {
         self.offset_in_firmware
@@ -37,6 +53,14 @@ impl FirmwareRegion for PciExpansionRom {
     fn region_size(&self) -> u64 {
         self.data_header.image_length as u64 * 512
     }
+
+    fn kind(&self) -> &'static str {
+        if self.is_open_firmware() {
+            "open-firmware"
+        } else {
+            "legacy"
+        }
+    }
 }
 
 #[derive(BinRead, Debug, Clone, Serialize)]
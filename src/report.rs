@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+
+use crate::firmware::{FirmwareBundleInfo, FirmwareInfo, LegacyPciImageInfo, TableStatus};
+use crate::FirmwareRegion;
+use std::io::Write;
+
+impl FirmwareBundleInfo {
+    /// Writes a plain text map of the parsed regions and structures, meant to be pasted into issues.
+    /// The format is stable: one item per line, offsets in hex and sizes in decimal.
+    pub fn write_report<W: Write>(&self, w: &mut W) -> crate::Result<()> {
+        let summary = self.summary();
+        writeln!(w, "firmwares: {}", summary.firmware_count)?;
+        writeln!(w, "pci images: {}", summary.pci_image_count)?;
+        if let (Some(vendor_id), Some(device_id)) = (summary.vendor_id, summary.device_id) {
+            writeln!(w, "device: {:04X}:{:04X}", vendor_id, device_id)?;
+        }
+        if let Some(version) = &summary.vbios_version {
+            writeln!(w, "version: {}", version)?;
+        }
+
+        writeln!(w)?;
+        writeln!(w, "regions:")?;
+        let regions = self.all_regions();
+        for region in &regions {
+            writeln!(
+                w,
+                "  {:#010X} {:>9} {}",
+                region.offset_in_firmware(),
+                region.region_size(),
+                region.kind()
+            )?;
+        }
+        for pair in regions.windows(2) {
+            let (end, next) = (
+                pair[0].end_offset_in_firmware(),
+                pair[1].offset_in_firmware(),
+            );
+            if end < next {
+                writeln!(w, "  {:#010X} {:>9} gap", end, next - end)?;
+            } else if end > next && pair[1].end_offset_in_firmware() <= end {
+                // NVGI regions contain the images that follow them
+                continue;
+            } else if end > next {
+                writeln!(w, "  {:#010X} {:>9} overlap", next, end - next)?;
+            }
+        }
+
+        for (index, firmware) in self.firmwares.iter().enumerate() {
+            writeln!(w)?;
+            writeln!(w, "firmware {}:", index)?;
+            write_firmware(w, firmware)?;
+        }
+
+        if let Some(plat_info) = self.nbsi_plat_info() {
+            writeln!(w)?;
+            writeln!(w, "nbsi plat info:")?;
+            writeln!(w, "  subsystem id {}", plat_info.subsystem_id)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_firmware<W: Write>(w: &mut W, firmware: &FirmwareInfo) -> crate::Result<()> {
+    for nvgi in &firmware.nvgi_regions {
+        writeln!(
+            w,
+            "  nvgi at {:#010X}: version {:#06X}",
+            nvgi.offset_in_firmware,
+            nvgi.version()
+        )?;
+    }
+    if let Some(rfrd) = &firmware.rfrd_region {
+        writeln!(
+            w,
+            "  rfrd at {:#010X}: pci rom offset {:#X}",
+            rfrd.offset_in_firmware, rfrd.header.pci_rom_offset
+        )?;
+    }
+    for image in firmware.pci_images() {
+        writeln!(
+            w,
+            "  {} image at {:#010X}: {} bytes",
+            image.kind(),
+            image.offset_in_firmware(),
+            image.region_size()
+        )?;
+    }
+    if let Some(image) = &firmware.legacy_pci_image {
+        write_legacy_image(w, image)?;
+    }
+    Ok(())
+}
+
+fn write_legacy_image<W: Write>(w: &mut W, image: &LegacyPciImageInfo) -> crate::Result<()> {
+    if image.bit_table_structure.is_some() {
+        writeln!(w, "  bit tokens:")?;
+    }
+    for (version, token) in image.bit_tokens_with_versions() {
+        writeln!(w, "    token {} version {}", token.name(), version)?;
+    }
+    writeln!(w, "  structures:")?;
+    if image.device_control_block.is_some() {
+        writeln!(w, "    device control block")?;
+    }
+    for (table, status) in image.table_statuses() {
+        match status {
+            TableStatus::Present => writeln!(w, "    {}", table)?,
+            TableStatus::Failed(error) => writeln!(w, "    {} failed: {}", table, error)?,
+            TableStatus::Absent => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: MIT
+
+use crate::cursor::OffsetReader;
+use crate::firmware::FirmwareBundleInfo;
+use crate::{read_available, Error, FirmwareRegion};
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+
+pub const CHECKSUM_CHECK: &str = "checksum";
+pub const IMAGE_CHAIN_CHECK: &str = "image chain";
+pub const LAYOUT_CHECK: &str = "layout";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    /// Offset of the offending bytes in the firmware file
+    pub offset: Option<u64>,
+    /// Failure description, `None` if the check passed
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(check: &'static str) -> Self {
+        Self {
+            check,
+            offset: None,
+            error: None,
+        }
+    }
+
+    fn fail(check: &'static str, offset: u64, error: String) -> Self {
+        Self {
+            check,
+            offset: Some(offset),
+            error: Some(error),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Checksum byte rewritten by [`FirmwareBundleInfo::fix_checksums`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChecksumFix {
+    pub structure: &'static str,
+    /// Offset of the checksum byte in the firmware file
+    pub offset: u64,
+    pub old_value: u8,
+    pub new_value: u8,
+}
+
+impl FirmwareBundleInfo {
+    /// Rewrites the last byte of every PCI image so its byte sum is zero. `rom` is the whole file
+    /// the bundle was parsed from, including the preamble.
+    pub fn fix_checksums(&self, rom: &mut [u8]) -> crate::Result<Vec<ChecksumFix>> {
+        let mut fixes = Vec::new();
+        for image in self
+            .firmwares
+            .iter()
+            .flat_map(|firmware| firmware.pci_images())
+        {
+            let offset = self.preamble_len + image.offset_in_firmware();
+            let end = offset + image.region_size();
+            let bytes = rom
+                .get_mut(offset as usize..end as usize)
+                .filter(|bytes| !bytes.is_empty())
+                .ok_or_else(|| {
+                    Error::InvalidFormat(format!(
+                        "Image {:#X}..{:#X} is outside of the file",
+                        offset, end
+                    ))
+                })?;
+            let checksum_index = bytes.len() - 1;
+            fixes.extend(fix_sum(bytes, checksum_index, image.kind(), offset));
+        }
+        Ok(fixes)
+    }
+
+    /// Runs the checksum, image chain and layout checks against the source the bundle was parsed from.
+    /// Every check reports either a single pass or one failure per offending region.
+    /// The NBSI hash is not checked, its algorithm is unknown.
+    pub fn validate<S: Read + Seek>(&self, source: &mut S) -> crate::Result<Vec<CheckResult>> {
+        let source = &mut OffsetReader::new(source, self.preamble_len);
+        let source_len = source.seek(SeekFrom::End(0))?;
+        let mut results = Vec::new();
+        results.extend(or_pass(CHECKSUM_CHECK, self.checksum_failures(source)?));
+        results.extend(or_pass(IMAGE_CHAIN_CHECK, self.image_chain_failures()));
+        results.extend(or_pass(LAYOUT_CHECK, self.layout_failures(source_len)));
+        Ok(results)
+    }
+
+    // Only the legacy x86 image is required to have a zero byte sum
+    fn checksum_failures<S: Read + Seek>(&self, source: &mut S) -> crate::Result<Vec<CheckResult>> {
+        let mut failures = Vec::new();
+        for image in self
+            .firmwares
+            .iter()
+            .filter_map(|firmware| firmware.legacy_pci_image.as_ref())
+        {
+            let offset = image.image.offset_in_firmware();
+            let mut bytes = vec![0u8; image.image.region_size() as usize];
+            source.seek(SeekFrom::Start(offset))?;
+            let len = read_available(source, &mut bytes);
+            if len < bytes.len() {
+                failures.push(CheckResult::fail(
+                    CHECKSUM_CHECK,
+                    offset,
+                    format!("Image is truncated to {} of {} bytes", len, bytes.len()),
+                ));
+                continue;
+            }
+            let sum = bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+            if sum != 0 {
+                failures.push(CheckResult::fail(
+                    CHECKSUM_CHECK,
+                    offset,
+                    format!("Image byte sum is {:#04X} instead of 0", sum),
+                ));
+            }
+        }
+        Ok(failures)
+    }
+
+    fn image_chain_failures(&self) -> Vec<CheckResult> {
+        let mut failures = Vec::new();
+        for firmware in &self.firmwares {
+            let mut images = firmware.pci_images();
+            images.sort_by_key(|image| image.offset_in_firmware());
+            for pair in images.windows(2) {
+                if pair[0].end_offset_in_firmware() != pair[1].offset_in_firmware() {
+                    failures.push(CheckResult::fail(
+                        IMAGE_CHAIN_CHECK,
+                        pair[0].end_offset_in_firmware(),
+                        format!(
+                            "Image at {:#X} ends at {:#X}, the next image starts at {:#X}",
+                            pair[0].offset_in_firmware(),
+                            pair[0].end_offset_in_firmware(),
+                            pair[1].offset_in_firmware()
+                        ),
+                    ));
+                }
+            }
+        }
+        failures
+    }
+
+    // NVGI regions wrap the images that follow them, so only the images are checked for overlaps
+    fn layout_failures(&self, source_len: u64) -> Vec<CheckResult> {
+        let mut failures = Vec::new();
+        let mut images: Vec<&dyn FirmwareRegion> = self
+            .firmwares
+            .iter()
+            .flat_map(|firmware| firmware.pci_images())
+            .collect();
+        if let Some(nbsi) = &self.nbsi_pci_expansion_rom {
+            images.push(nbsi);
+        }
+        images.sort_by_key(|image| image.offset_in_firmware());
+        for pair in images.windows(2) {
+            if pair[0].end_offset_in_firmware() > pair[1].offset_in_firmware() {
+                failures.push(CheckResult::fail(
+                    LAYOUT_CHECK,
+                    pair[1].offset_in_firmware(),
+                    format!(
+                        "Image at {:#X} overlaps image {:#X}..{:#X}",
+                        pair[1].offset_in_firmware(),
+                        pair[0].offset_in_firmware(),
+                        pair[0].end_offset_in_firmware()
+                    ),
+                ));
+            }
+        }
+        for region in self.all_regions() {
+            if region.end_offset_in_firmware() > source_len {
+                failures.push(CheckResult::fail(
+                    LAYOUT_CHECK,
+                    region.offset_in_firmware(),
+                    format!(
+                        "Region ends at {:#X} after the end of the file at {:#X}",
+                        region.end_offset_in_firmware(),
+                        source_len
+                    ),
+                ));
+            }
+        }
+        failures
+    }
+}
+
+fn fix_sum(
+    bytes: &mut [u8],
+    checksum_index: usize,
+    structure: &'static str,
+    offset: u64,
+) -> Option<ChecksumFix> {
+    let sum = bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    if sum == 0 {
+        return None;
+    }
+    let old_value = bytes[checksum_index];
+    let new_value = old_value.wrapping_sub(sum);
+    bytes[checksum_index] = new_value;
+    Some(ChecksumFix {
+        structure,
+        offset: offset + checksum_index as u64,
+        old_value,
+        new_value,
+    })
+}
+
+fn or_pass(check: &'static str, failures: Vec<CheckResult>) -> Vec<CheckResult> {
+    if failures.is_empty() {
+        vec![CheckResult::pass(check)]
+    } else {
+        failures
+    }
+}